@@ -0,0 +1,69 @@
+use crate::config::Config;
+use crate::error::Result;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Default freshness window for cached reference data
+pub const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+pub fn cache_dir() -> PathBuf {
+    Config::config_dir().join("cache")
+}
+
+/// Map an arbitrary cache key (URL, path, name) to a file under the cache dir
+pub fn file_for(key: &str) -> PathBuf {
+    let name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    cache_dir().join(format!("{name}.json"))
+}
+
+/// Load a cached value. With a TTL, entries older than the TTL are ignored.
+pub fn load(key: &str, ttl: Option<Duration>) -> Option<Value> {
+    let path = file_for(key);
+    if let Some(ttl) = ttl {
+        let modified = std::fs::metadata(&path).ok()?.modified().ok()?;
+        let age = modified.elapsed().ok()?;
+        if age > ttl {
+            return None;
+        }
+    }
+    let content = std::fs::read_to_string(&path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub fn store(key: &str, value: &Value) -> Result<()> {
+    std::fs::create_dir_all(cache_dir())?;
+    let content = serde_json::to_string_pretty(value)?;
+    std::fs::write(file_for(key), content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_for_sanitizes_key() {
+        let path = file_for("http://localhost:8000/v1/platform/countries");
+        assert_eq!(
+            path.file_name().unwrap().to_string_lossy(),
+            "http___localhost_8000_v1_platform_countries.json"
+        );
+        assert!(path.starts_with(cache_dir()));
+    }
+
+    #[test]
+    fn test_file_for_keeps_simple_names() {
+        let path = file_for("last-quote");
+        assert_eq!(path.file_name().unwrap().to_string_lossy(), "last-quote.json");
+    }
+}
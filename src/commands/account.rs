@@ -1,12 +1,19 @@
 use crate::client::ArkyClient;
-use crate::commands::parse_data;
-use crate::error::Result;
+use crate::commands::{parse_data, print_list, validate_address, ListArgs};
+use crate::error::{CliError, Result};
 use crate::output::Format;
 use clap::Subcommand;
-use serde_json::json;
+use serde_json::{json, Value};
 
 #[derive(Subcommand, Debug)]
 pub enum AccountCommand {
+    /// Show the current account (same as `arky auth whoami`)
+    #[command(long_about = "Display the account associated with the current token.\n\n\
+        Same as `arky auth whoami`.\n\n\
+        Example:\n\
+        arky account me\n\n\
+        Response: {\"id\": \"acc_123\", \"email\": \"user@example.com\", \"name\": \"...\"}")]
+    Me,
     /// Search accounts
     #[command(long_about = "Search for accounts by query.\n\n\
         Examples:\n\
@@ -17,10 +24,8 @@ pub enum AccountCommand {
     Search {
         #[arg(long)]
         query: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
     },
     /// Update the current account
     #[command(long_about = "Update the current account profile.\n\n\
@@ -28,6 +33,7 @@ pub enum AccountCommand {
           phoneNumbers  Array of phone number strings\n\
           addresses     Array of address objects\n\
           apiTokens     Array of API tokens (null to clear)\n\n\
+        To add or remove a single address, use `arky account addresses`.\n\n\
         Example:\n\
         arky account update --data '{\"phoneNumbers\": [\"+1234567890\"]}'")]
     Update {
@@ -65,35 +71,62 @@ pub enum AccountCommand {
         #[arg(long)]
         code: String,
     },
+    /// Manage saved addresses on the current account
+    Addresses {
+        #[command(subcommand)]
+        cmd: AddressCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AddressCommand {
+    /// List saved addresses
+    #[command(long_about = "List the addresses saved on the current account, in index order.\n\n\
+        Example:\n\
+        arky account addresses list")]
+    List,
+    /// Add an address
+    #[command(long_about = "Append an address to the current account, keeping the others intact.\n\n\
+        Required (--data JSON):\n\
+          name, street1, city, postalCode, country (ISO 2-letter)\n\n\
+        Optional: street2, state, phone\n\n\
+        Example:\n\
+        arky account addresses add --data '{\n\
+          \"name\": \"Home\", \"street1\": \"456 Oak Ave\",\n\
+          \"city\": \"LA\", \"state\": \"CA\", \"postalCode\": \"90001\", \"country\": \"US\"\n\
+        }'")]
+    Add {
+        #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
+        data: Option<String>,
+    },
+    /// Remove an address by index
+    #[command(long_about = "Remove one address from the current account.\n\n\
+        The index is the 0-based position shown by `arky account addresses list`.\n\n\
+        Example:\n\
+        arky account addresses remove --index 1")]
+    Remove {
+        #[arg(long)]
+        index: usize,
+    },
 }
 
 pub async fn handle(cmd: AccountCommand, client: &ArkyClient, format: &Format) -> Result<()> {
     match cmd {
-        AccountCommand::Search {
-            query,
-            limit,
-            cursor,
-        } => {
+        AccountCommand::Me => show_me(client, format).await?,
+        AccountCommand::Search { query, list } => {
             let biz_id = client.require_business_id()?;
-            let mut params: Vec<(&str, String)> = vec![
-                ("limit", limit.to_string()),
-                ("businessId", biz_id.to_string()),
-            ];
+            let mut params: Vec<(&str, String)> = vec![("businessId", biz_id.to_string())];
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client.get("/v1/accounts/search", &params_ref).await?;
-            crate::output::print_output(&result, format);
+            print_list(client, "/v1/accounts/search", &params_ref, &list, format).await?;
         }
         AccountCommand::Update { data } => {
             let body = parse_data(data.as_deref())?;
             let result = client.put("/v1/accounts", &body).await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         AccountCommand::Delete => {
             let _ = client.delete("/v1/accounts").await?;
@@ -111,6 +144,91 @@ pub async fn handle(cmd: AccountCommand, client: &ArkyClient, format: &Format) -
                 .await?;
             crate::output::print_success("Phone number confirmed");
         }
+        AccountCommand::Addresses { cmd } => handle_addresses(cmd, client, format).await?,
+    }
+    Ok(())
+}
+
+async fn handle_addresses(cmd: AddressCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    let mut account = client.get("/v1/accounts/me", &[]).await?;
+    let mut addresses = account["addresses"].as_array().cloned().unwrap_or_default();
+
+    match cmd {
+        AddressCommand::List => {
+            crate::output::print_output(&Value::Array(addresses), format)?;
+            return Ok(());
+        }
+        AddressCommand::Add { data } => {
+            let address = parse_data(data.as_deref())?;
+            validate_address(&address, "Address")?;
+            addresses.push(address);
+        }
+        AddressCommand::Remove { index } => {
+            if index >= addresses.len() {
+                return Err(CliError::InvalidInput(format!(
+                    "No address at index {index}; the account has {} address(es)",
+                    addresses.len()
+                )));
+            }
+            addresses.remove(index);
+        }
     }
+
+    account["addresses"] = Value::Array(addresses);
+    let result = client.put("/v1/accounts", &account).await?;
+    crate::output::print_output(&result, format)?;
+    Ok(())
+}
+
+/// Print the current account; backs both `account me` and `auth whoami`
+pub async fn show_me(client: &ArkyClient, format: &Format) -> Result<()> {
+    let result = client.get("/v1/accounts/me", &[]).await?;
+    crate::output::print_output(&result, format)?;
     Ok(())
 }
+
+/// Resolve `--account-email` to an account ID via account search. Only exact
+/// (case-insensitive) email matches count; zero or several is an error.
+pub async fn resolve_account_email(client: &ArkyClient, email: &str) -> Result<String> {
+    let biz_id = client.require_business_id()?;
+    let params = [("businessId", biz_id), ("query", email), ("limit", "50")];
+    let result = client.get("/v1/accounts/search", &params).await?;
+    let ids = matching_account_ids(&result, email);
+    match ids.as_slice() {
+        [id] => Ok(id.to_string()),
+        [] => Err(CliError::InvalidInput(format!(
+            "No account found with email {email}. Use --account-id instead"
+        ))),
+        _ => Err(CliError::InvalidInput(format!(
+            "{} accounts match email {email} ({}). Use --account-id instead",
+            ids.len(),
+            ids.join(", ")
+        ))),
+    }
+}
+
+fn matching_account_ids<'a>(result: &'a Value, email: &str) -> Vec<&'a str> {
+    result["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|a| a["email"].as_str().is_some_and(|e| e.eq_ignore_ascii_case(email.trim())))
+        .filter_map(|a| a["id"].as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_account_ids() {
+        let result = json!({"data": [
+            {"id": "a1", "email": "Jane@Example.com"},
+            {"id": "a2", "email": "jane@example.com.au"},
+            {"id": "a3"},
+        ]});
+        assert_eq!(matching_account_ids(&result, "jane@example.com"), vec!["a1"]);
+        assert!(matching_account_ids(&result, "bob@example.com").is_empty());
+    }
+}
@@ -1,9 +1,17 @@
 use crate::client::ArkyClient;
-use crate::commands::{merge_data, parse_data};
+use crate::commands::{
+    delete_entity, merge_data, parse_data, print_created, print_example, print_list, Example,
+    ListArgs,
+};
 use crate::error::Result;
 use crate::output::Format;
 use clap::Subcommand;
-use serde_json::json;
+use serde_json::{json, Value};
+use std::io::{BufRead, IsTerminal, Write};
+use std::time::Duration;
+
+/// Agent runs (LLM + tool calls) can take minutes, so they get a longer default
+const RUN_TIMEOUT: Duration = Duration::from_secs(600);
 
 #[derive(Subcommand, Debug)]
 pub enum AgentCommand {
@@ -28,10 +36,8 @@ pub enum AgentCommand {
         arky agent list --cursor CURSOR_TOKEN\n\n\
         Response: {\"items\": [...], \"cursor\": \"...\"}")]
     List {
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
     },
     /// Create an agent
     #[command(long_about = "Create an AI agent.\n\n\
@@ -62,9 +68,14 @@ pub enum AgentCommand {
         }'")]
     Create {
         /// Agent key
+        #[arg(required_unless_present = "example", default_value = "")]
         key: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Print only the created ID, e.g. for ID=$(arky ... --id-only)")]
+        id_only: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Update an agent
     #[command(long_about = "Update an AI agent by ID.\n\n\
@@ -76,9 +87,12 @@ pub enum AgentCommand {
         arky agent update AGENT_ID --data @agent.json")]
     Update {
         /// Agent ID
+        #[arg(required_unless_present = "example", default_value = "")]
         id: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Delete an agent
     #[command(long_about = "Delete an AI agent by ID.\n\n\
@@ -87,6 +101,8 @@ pub enum AgentCommand {
     Delete {
         /// Agent ID
         id: String,
+        #[arg(long, help = "Treat an already-deleted ID (404) as success")]
+        ignore_missing: bool,
     },
     /// Run an agent with a message
     #[command(long_about = "Run an AI agent with a message and get a response.\n\n\
@@ -95,12 +111,34 @@ pub enum AgentCommand {
         Examples:\n\
         arky agent run AGENT_ID --data '{\"message\": \"What services do you offer?\"}'\n\
         arky agent run AGENT_ID --data '{\"message\": \"How many products are there?\"}'\n\
-        echo '{\"message\": \"Hello\"}' | arky agent run AGENT_ID --data -")]
+        echo '{\"message\": \"Hello\"}' | arky agent run AGENT_ID --data -\n\n\
+        Runs wait up to 10 minutes by default; override with --timeout <secs>:\n\
+        arky agent run AGENT_ID --timeout 1200 --data @prompt.json\n\n\
+        Streaming:\n\
+          --stream prints the reply to stdout as it is generated (Server-Sent Events),\n\
+          then the assembled result. With --stream the timeout applies between\n\
+          chunks, not to the whole run. Falls back to a normal response if the\n\
+          server doesn't stream.\n\
+        arky agent run AGENT_ID --stream --data '{\"message\": \"Hello\"}'")]
     Run {
         /// Agent ID
         id: String,
         #[arg(long, help = "JSON data with 'message' field, or inline, @file, - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Stream the reply token-by-token as it arrives")]
+        stream: bool,
+    },
+    /// Chat with an agent interactively
+    #[command(long_about = "Start an interactive chat session with an agent.\n\n\
+        Each line read from stdin is sent as a message; replies are printed as\n\
+        they come back. The conversation id returned by the server is carried\n\
+        across turns so the agent keeps context. End with /exit or Ctrl-D.\n\n\
+        Examples:\n\
+        arky agent chat AGENT_ID\n\
+        printf 'Hi\\nWhat do you sell?\\n' | arky agent chat AGENT_ID")]
+    Chat {
+        /// Agent ID
+        id: String,
     },
     /// List agent memories
     #[command(long_about = "List memories stored by an agent.\n\n\
@@ -117,8 +155,8 @@ pub enum AgentCommand {
         id: String,
         #[arg(long, help = "Filter: soul, message, fact")]
         category: Option<String>,
-        #[arg(long, default_value = "100")]
-        limit: u32,
+        #[command(flatten)]
+        list: ListArgs,
     },
     /// Delete a specific memory
     #[command(name = "delete-memory", long_about = "Delete a specific memory from an agent.\n\n\
@@ -133,6 +171,9 @@ pub enum AgentCommand {
 }
 
 pub async fn handle(cmd: AgentCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    if cmd.is_example() {
+        return print_example(&example());
+    }
     let biz_id = client.require_business_id()?;
 
     match cmd {
@@ -140,69 +181,124 @@ pub async fn handle(cmd: AgentCommand, client: &ArkyClient, format: &Format) ->
             let result = client
                 .get(&format!("/v1/businesses/{biz_id}/agents/{id}"), &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
-        AgentCommand::List { limit, cursor } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
-            let params_ref: Vec<(&str, &str)> =
-                params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(&format!("/v1/businesses/{biz_id}/agents"), &params_ref)
+        AgentCommand::List { list } => {
+            print_list(client, &format!("/v1/businesses/{biz_id}/agents"), &[], &list, format)
                 .await?;
-            crate::output::print_output(&result, format);
         }
-        AgentCommand::Create { key, data } => {
+        AgentCommand::Create {
+            key, data, id_only, ..
+        } => {
             let mut body = json!({ "key": key, "businessId": biz_id });
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
+            merge_data(&mut body, overlay)?;
             let result = client
                 .post(&format!("/v1/businesses/{biz_id}/agents"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            print_created(&result, "agent", id_only, format)?;
         }
-        AgentCommand::Update { id, data } => {
+        AgentCommand::Update { id, data, .. } => {
             let mut body = json!({ "id": id });
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
+            merge_data(&mut body, overlay)?;
             let result = client
                 .put(&format!("/v1/businesses/{biz_id}/agents/{id}"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
-        AgentCommand::Delete { id } => {
-            let _ = client
-                .delete(&format!("/v1/businesses/{biz_id}/agents/{id}"))
-                .await?;
-            crate::output::print_success("Agent deleted");
+        AgentCommand::Delete { id, ignore_missing } => {
+            let path = format!("/v1/businesses/{biz_id}/agents/{id}");
+            if delete_entity(client, &path, "agent", ignore_missing).await? {
+                crate::output::print_success("Agent deleted");
+            }
         }
-        AgentCommand::Run { id, data } => {
+        AgentCommand::Run { id, data, stream } => {
             let body = parse_data(data.as_deref())?;
-            let result = client
-                .post(&format!("/v1/businesses/{biz_id}/agents/{id}/run"), &body)
+            let path = format!("/v1/businesses/{biz_id}/agents/{id}/run");
+            let timeout = client.timeout_or(RUN_TIMEOUT);
+            if !stream {
+                let result = client.post_with_timeout(&path, &body, timeout).await?;
+                crate::output::print_output(&result, format)?;
+                return Ok(());
+            }
+
+            let mut text = String::new();
+            let mut final_result: Option<Value> = None;
+            let fallback = client
+                .post_stream(&path, &body, timeout, |data| {
+                    let event: Value = serde_json::from_str(data).unwrap_or_else(|_| json!(data));
+                    if let Some(delta) = delta_text(&event) {
+                        print!("{delta}");
+                        let _ = std::io::stdout().flush();
+                        text.push_str(delta);
+                    }
+                    if let Some(result) = event.get("result") {
+                        final_result = Some(result.clone());
+                    }
+                })
                 .await?;
-            crate::output::print_output(&result, format);
+
+            match fallback {
+                Some(result) => crate::output::print_output(&result, format)?,
+                None => {
+                    println!();
+                    let result = final_result.unwrap_or_else(|| json!({ "message": text }));
+                    crate::output::print_output(&result, format)?;
+                }
+            }
         }
-        AgentCommand::Memories {
-            id,
-            category,
-            limit,
-        } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+        AgentCommand::Chat { id } => {
+            let path = format!("/v1/businesses/{biz_id}/agents/{id}/run");
+            let timeout = client.timeout_or(RUN_TIMEOUT);
+            let interactive = std::io::stdin().is_terminal();
+            let mut conversation: Option<(&str, String)> = None;
+
+            let mut lines = std::io::stdin().lock().lines();
+            loop {
+                if interactive {
+                    eprint!("> ");
+                    let _ = std::io::stderr().flush();
+                }
+                let Some(line) = lines.next() else { break };
+                let line = line?;
+                let message = line.trim();
+                if message == "/exit" || message == "/quit" {
+                    break;
+                }
+                if message.is_empty() {
+                    continue;
+                }
+
+                let mut body = json!({ "message": message });
+                if let Some((key, ref value)) = conversation {
+                    body[key] = json!(value);
+                }
+                let result = client.post_with_timeout(&path, &body, timeout).await?;
+                if let Some(found) = conversation_id(&result) {
+                    conversation = Some(found);
+                }
+                match reply_text(&result) {
+                    Some(text) => println!("{text}"),
+                    None => crate::output::print_output(&result, format)?,
+                }
+            }
+        }
+        AgentCommand::Memories { id, category, list } => {
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref c) = category {
                 params.push(("category", c.clone()));
             }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(
-                    &format!("/v1/businesses/{biz_id}/agents/{id}/memories"),
-                    &params_ref,
-                )
-                .await?;
-            crate::output::print_output(&result, format);
+            print_list(
+                client,
+                &format!("/v1/businesses/{biz_id}/agents/{id}/memories"),
+                &params_ref,
+                &list.with_default_limit(100),
+                format,
+            )
+            .await?;
         }
         AgentCommand::DeleteMemory { id, memory_id } => {
             let _ = client
@@ -215,3 +311,62 @@ pub async fn handle(cmd: AgentCommand, client: &ArkyClient, format: &Format) ->
     }
     Ok(())
 }
+
+fn example() -> Value {
+    json!({
+        "rolePrompt": "You are a helpful sales assistant.",
+        "status": "active",
+        "provider": { "type": "deep_seek", "integrationId": "INT_ID", "model": "deepseek-chat" },
+        "toolsConfig": ["integration", "web_search", "read_webpage", "memory"]
+    })
+}
+
+/// Text carried by one streamed event: `{"delta": "..."}`,
+/// `{"delta": {"content": "..."}}`, or a bare string payload
+fn delta_text(event: &Value) -> Option<&str> {
+    match &event["delta"] {
+        Value::String(s) => Some(s),
+        Value::Object(d) => d.get("content").and_then(|c| c.as_str()),
+        _ => event.as_str(),
+    }
+}
+
+/// Keys the run endpoint may use for the conversation/session id
+const CONVERSATION_KEYS: &[&str] = &["conversationId", "sessionId", "chatId"];
+
+/// Find the conversation id in a run response, returning the key it came
+/// under so the next turn sends it back the same way
+fn conversation_id(result: &Value) -> Option<(&'static str, String)> {
+    CONVERSATION_KEYS.iter().find_map(|key| {
+        result[*key].as_str().map(|id| (*key, id.to_string()))
+    })
+}
+
+/// The agent's reply text, if the response carries one
+fn reply_text(result: &Value) -> Option<&str> {
+    ["message", "reply", "response", "content", "text"]
+        .iter()
+        .find_map(|key| result[*key].as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversation_id_and_reply() {
+        let result = json!({"sessionId": "s1", "reply": "Hello!"});
+        assert_eq!(conversation_id(&result), Some(("sessionId", "s1".to_string())));
+        assert_eq!(reply_text(&result), Some("Hello!"));
+        assert_eq!(conversation_id(&json!({"message": "x"})), None);
+        assert_eq!(reply_text(&json!({"data": {}})), None);
+    }
+
+    #[test]
+    fn test_delta_text() {
+        assert_eq!(delta_text(&json!({"delta": "Hi"})), Some("Hi"));
+        assert_eq!(delta_text(&json!({"delta": {"content": "there"}})), Some("there"));
+        assert_eq!(delta_text(&json!("raw")), Some("raw"));
+        assert_eq!(delta_text(&json!({"result": {}})), None);
+    }
+}
@@ -1,9 +1,12 @@
 use crate::client::ArkyClient;
-use crate::commands::{merge_data, parse_data};
+use crate::commands::{
+    confirm_delete, create_or_update, delete_entity, delete_from_stdin, merge_data, parse_data,
+    print_created, print_example, print_list, DeleteArgs, Example, ListArgs,
+};
 use crate::error::Result;
 use crate::output::Format;
 use clap::Subcommand;
-use serde_json::json;
+use serde_json::{json, Value};
 
 #[derive(Subcommand, Debug)]
 pub enum AudienceCommand {
@@ -26,10 +29,8 @@ pub enum AudienceCommand {
     List {
         #[arg(long)]
         query: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
     },
     /// Create an audience (access group with optional subscription pricing)
     #[command(long_about = "Create an audience for access control and subscriptions.\n\n\
@@ -54,9 +55,16 @@ pub enum AudienceCommand {
         }'")]
     Create {
         /// Audience key (unique within business)
+        #[arg(required_unless_present = "example", default_value = "")]
         key: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Print only the created ID, e.g. for ID=$(arky ... --id-only)")]
+        id_only: bool,
+        #[arg(long, help = "If KEY is taken, update that audience instead of failing")]
+        upsert: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Update an audience
     #[command(long_about = "Update an audience by ID.\n\n\
@@ -69,14 +77,29 @@ pub enum AudienceCommand {
         arky audience update AUD_ID --data '{\"prices\": [], \"status\": \"active\"}'")]
     Update {
         /// Audience ID
+        #[arg(required_unless_present = "example", default_value = "")]
         id: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Delete an audience
+    #[command(long_about = "Delete an audience by ID.\n\n\
+        --show fetches it first, prints its id/key/status/title and asks before\n\
+        deleting; add --yes to skip the question.\n\n\
+        --stdin deletes every ID piped in (one per line, or a JSON array of IDs or\n\
+        objects with an id), reporting each and continuing past failures unless\n\
+        --stop-on-error is given.\n\n\
+        Examples:\n\
+        arky audience delete AUD_ID --show\n\
+        arky audience list --all --pointer /data | arky audience delete --stdin")]
     Delete {
         /// Audience ID
+        #[arg(required_unless_present = "stdin", default_value = "")]
         id: String,
+        #[command(flatten)]
+        delete: DeleteArgs,
     },
     /// List subscribers of an audience
     #[command(long_about = "List accounts subscribed to an audience.\n\n\
@@ -88,10 +111,8 @@ pub enum AudienceCommand {
     Subscribers {
         /// Audience ID
         id: String,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
     },
     /// Add a subscriber to an audience
     #[command(name = "add-subscriber", long_about = "Add a subscriber to an audience.\n\n\
@@ -123,6 +144,9 @@ pub enum AudienceCommand {
 }
 
 pub async fn handle(cmd: AudienceCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    if cmd.is_example() {
+        return print_example(&example());
+    }
     let biz_id = client.require_business_id()?;
 
     match cmd {
@@ -130,65 +154,69 @@ pub async fn handle(cmd: AudienceCommand, client: &ArkyClient, format: &Format)
             let result = client
                 .get(&format!("/v1/businesses/{biz_id}/audiences/{id}"), &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
-        AudienceCommand::List {
-            query,
-            limit,
-            cursor,
-        } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+        AudienceCommand::List { query, list } => {
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(&format!("/v1/businesses/{biz_id}/audiences"), &params_ref)
-                .await?;
-            crate::output::print_output(&result, format);
+            print_list(
+                client,
+                &format!("/v1/businesses/{biz_id}/audiences"),
+                &params_ref,
+                &list,
+                format,
+            )
+            .await?;
         }
-        AudienceCommand::Create { key, data } => {
+        AudienceCommand::Create {
+            key,
+            data,
+            id_only,
+            upsert,
+            ..
+        } => {
             let mut body = json!({ "key": key });
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
-            let result = client
-                .post(&format!("/v1/businesses/{biz_id}/audiences"), &body)
-                .await?;
-            crate::output::print_output(&result, format);
+            merge_data(&mut body, overlay)?;
+            let path = format!("/v1/businesses/{biz_id}/audiences");
+            let result = create_or_update(client, &path, "audience", &body, upsert).await?;
+            print_created(&result, "audience", id_only, format)?;
         }
-        AudienceCommand::Update { id, data } => {
+        AudienceCommand::Update { id, data, .. } => {
             let mut body = json!({ "id": id });
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
+            merge_data(&mut body, overlay)?;
             let result = client
                 .put(&format!("/v1/businesses/{biz_id}/audiences/{id}"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
-        }
-        AudienceCommand::Delete { id } => {
-            let _ = client
-                .delete(&format!("/v1/businesses/{biz_id}/audiences/{id}"))
-                .await?;
-            crate::output::print_success("Audience deleted");
+            crate::output::print_output(&result, format)?;
         }
-        AudienceCommand::Subscribers { id, limit, cursor } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
+        AudienceCommand::Delete { id, delete } => {
+            let collection = format!("/v1/businesses/{biz_id}/audiences");
+            if delete.stdin {
+                return delete_from_stdin(client, &collection, "audience", &delete).await;
             }
-            let params_ref: Vec<(&str, &str)> =
-                params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(
-                    &format!("/v1/businesses/{biz_id}/audiences/{id}/subscribers"),
-                    &params_ref,
-                )
-                .await?;
-            crate::output::print_output(&result, format);
+            let path = format!("{collection}/{id}");
+            if !confirm_delete(client, &path, "audience", &delete, format).await? {
+                return Ok(());
+            }
+            if delete_entity(client, &path, "audience", delete.ignore_missing).await? {
+                crate::output::print_success("Audience deleted");
+            }
+        }
+        AudienceCommand::Subscribers { id, list } => {
+            print_list(
+                client,
+                &format!("/v1/businesses/{biz_id}/audiences/{id}/subscribers"),
+                &[],
+                &list,
+                format,
+            )
+            .await?;
         }
         AudienceCommand::AddSubscriber { id, data } => {
             let body = parse_data(data.as_deref())?;
@@ -198,7 +226,7 @@ pub async fn handle(cmd: AudienceCommand, client: &ArkyClient, format: &Format)
                     &body,
                 )
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         AudienceCommand::RemoveSubscriber { id, account_id } => {
             let _ = client
@@ -211,3 +239,18 @@ pub async fn handle(cmd: AudienceCommand, client: &ArkyClient, format: &Format)
     }
     Ok(())
 }
+
+fn example() -> Value {
+    json!({
+        "prices": [],
+        "blocks": [
+            {
+                "key": "title",
+                "type": "localized_text",
+                "id": "b1",
+                "properties": {},
+                "value": { "en": "Premium Members" }
+            }
+        ]
+    })
+}
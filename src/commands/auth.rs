@@ -1,9 +1,10 @@
 use crate::client::ArkyClient;
-use crate::config::Config;
+use crate::commands::account::show_me;
+use crate::config::{validate_profile_name, Config};
 use crate::error::Result;
 use crate::output::{print_success, Format};
 use clap::Subcommand;
-use serde_json::json;
+use serde_json::{json, Value};
 
 #[derive(Subcommand, Debug)]
 pub enum AuthCommand {
@@ -21,29 +22,43 @@ pub enum AuthCommand {
     /// Verify a magic link code and save the token
     #[command(long_about = "Verify email with the code received, get auth token.\n\n\
         This is step 2 of authentication. On success, the access token is\n\
-        automatically saved to ~/.arky/config.json for future requests.\n\n\
+        automatically saved to ~/.arky/config.json for future requests,\n\
+        along with the refresh token and account ID.\n\n\
+        --save-profile NAME saves them to ~/.arky/profiles/NAME.json instead;\n\
+        use that login later with --profile NAME.\n\n\
         Example:\n\
-        arky auth verify user@example.com 123456\n\n\
+        arky auth verify user@example.com 123456\n\
+        arky auth verify admin@example.com 123456 --save-profile admin\n\n\
         Response: {\"accessToken\": \"eyJ...\", \"refreshToken\": \"...\", \"accountId\": \"...\"}")]
     Verify {
         /// Email address
         email: String,
         /// Verification code received via email
         code: String,
+        #[arg(long, value_name = "NAME", help = "Save the token to this profile instead")]
+        save_profile: Option<String>,
     },
     /// Create an anonymous session (no email needed)
     #[command(long_about = "Create an anonymous session token.\n\n\
         Useful for public-facing operations that don't require a user account.\n\
-        The token is saved to ~/.arky/config.json.\n\n\
-        Example:\n\
-        arky auth session\n\n\
+        The token is saved to ~/.arky/config.json, or with --save-profile NAME to\n\
+        ~/.arky/profiles/NAME.json (used with --profile NAME), so an anonymous\n\
+        token can sit next to an admin login.\n\n\
+        Examples:\n\
+        arky auth session\n\
+        arky auth session --save-profile anon\n\
+        arky --profile anon product list\n\n\
         Response: {\"accessToken\": \"eyJ...\", \"accountId\": \"anon_...\"}")]
-    Session,
+    Session {
+        #[arg(long, value_name = "NAME", help = "Save the token to this profile instead")]
+        save_profile: Option<String>,
+    },
     /// Show current account info
     #[command(long_about = "Display the account associated with the current token.\n\n\
         Requires a valid token (set via login/verify, session, or --token flag).\n\n\
         Example:\n\
-        arky auth whoami\n\n\
+        arky auth whoami\n\
+        arky account me      # same thing\n\n\
         Response: {\"id\": \"acc_123\", \"email\": \"user@example.com\", \"name\": \"...\"}")]
     Whoami,
 }
@@ -55,39 +70,90 @@ pub async fn handle(cmd: AuthCommand, client: &ArkyClient, format: &Format) -> R
                 .post("/v1/auth/code", &json!({ "email": email }))
                 .await?;
             print_success(&format!("Code sent to {email}"));
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
-        AuthCommand::Verify { email, code } => {
+        AuthCommand::Verify {
+            email,
+            code,
+            save_profile,
+        } => {
+            if let Some(ref name) = save_profile {
+                validate_profile_name(name)?;
+            }
             let result = client
                 .post("/v1/auth/verify", &json!({ "email": email, "code": code }))
                 .await?;
-
-            // Save token to config
-            if let Some(token) = result.get("accessToken").and_then(|v| v.as_str()) {
-                let mut cfg = Config::load_file();
-                cfg.token = Some(token.to_string());
-                cfg.save_file()?;
-                print_success("Token saved to ~/.arky/config.json");
-            }
-
-            crate::output::print_output(&result, format);
+            save_session(&result, save_profile.as_deref(), "Token")?;
+            crate::output::print_output(&result, format)?;
         }
-        AuthCommand::Session => {
-            let result = client.post("/v1/auth/session", &json!({})).await?;
-
-            if let Some(token) = result.get("accessToken").and_then(|v| v.as_str()) {
-                let mut cfg = Config::load_file();
-                cfg.token = Some(token.to_string());
-                cfg.save_file()?;
-                print_success("Session token saved to ~/.arky/config.json");
+        AuthCommand::Session { save_profile } => {
+            if let Some(ref name) = save_profile {
+                validate_profile_name(name)?;
             }
+            let result = client.post("/v1/auth/session", &json!({})).await?;
+            save_session(&result, save_profile.as_deref(), "Session token")?;
+            crate::output::print_output(&result, format)?;
+        }
+        AuthCommand::Whoami => show_me(client, format).await?,
+    }
+    Ok(())
+}
 
-            crate::output::print_output(&result, format);
+/// Save an auth response's token to the active config, or to `profile`
+fn save_session(result: &Value, profile: Option<&str>, what: &str) -> Result<()> {
+    let mut cfg = match profile {
+        Some(name) => Config::load_profile(name)?,
+        None => Config::load_file(),
+    };
+    if !apply_session(&mut cfg, result) {
+        return Ok(());
+    }
+    let path = match profile {
+        Some(name) => {
+            cfg.save_profile(name)?;
+            Config::profile_path(name)
         }
-        AuthCommand::Whoami => {
-            let result = client.get("/v1/accounts/me", &[]).await?;
-            crate::output::print_output(&result, format);
+        None => {
+            cfg.save_file()?;
+            Config::config_path()
         }
-    }
+    };
+    print_success(&format!("{what} saved to {}", path.display()));
     Ok(())
 }
+
+/// Copy accessToken, refreshToken and accountId from an auth response into the
+/// config. The latter two are replaced together so a new session never keeps
+/// the previous login's refresh token. Returns false when there is no token.
+fn apply_session(cfg: &mut Config, result: &Value) -> bool {
+    let Some(token) = result["accessToken"].as_str() else {
+        return false;
+    };
+    let field = |name: &str| result[name].as_str().map(String::from);
+    cfg.token = Some(token.to_string());
+    cfg.refresh_token = field("refreshToken");
+    cfg.account_id = field("accountId");
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_session() {
+        let mut cfg = Config::default();
+        let login = json!({"accessToken": "a1", "refreshToken": "r1", "accountId": "acc_1"});
+        assert!(apply_session(&mut cfg, &login));
+        assert_eq!(cfg.refresh_token.as_deref(), Some("r1"));
+        assert_eq!(cfg.account_id.as_deref(), Some("acc_1"));
+
+        let anon = json!({"accessToken": "a2", "accountId": "anon_1"});
+        assert!(apply_session(&mut cfg, &anon));
+        assert_eq!(cfg.token.as_deref(), Some("a2"));
+        assert!(cfg.refresh_token.is_none());
+
+        assert!(!apply_session(&mut cfg, &json!({"success": true})));
+        assert_eq!(cfg.token.as_deref(), Some("a2"));
+    }
+}
@@ -1,9 +1,13 @@
 use crate::client::ArkyClient;
-use crate::commands::{merge_data, parse_data};
+use crate::commands::account::resolve_account_email;
+use crate::commands::{
+    load_last_quote, merge_data, parse_data, parse_time_arg, print_created, print_example,
+    print_list, save_last_quote, Example, ListArgs,
+};
 use crate::error::Result;
 use crate::output::Format;
 use clap::Subcommand;
-use serde_json::json;
+use serde_json::{json, Value};
 
 #[derive(Subcommand, Debug)]
 pub enum BookingCommand {
@@ -19,13 +23,19 @@ pub enum BookingCommand {
     },
     /// Search bookings
     #[command(long_about = "Search and filter bookings.\n\n\
-        Time range filters use epoch seconds.\n\
+        --from/--to (or --since/--until) accept epoch seconds, YYYY-MM-DD, now,\n\
+        or an offset from now: 7d, 12h (ago), +3d (ahead).\n\
+        --upcoming is shorthand for --from now, --past for --to now.\n\
         Statuses: pending, confirmed, cancelled, completed.\n\n\
         Examples:\n\
         arky booking list\n\
         arky booking list --service-id SVC_ID --from 1771405200 --to 1771410600\n\
         arky booking list --provider-id PROV_ID --status confirmed\n\
-        arky booking list --account-id ACC_ID")]
+        arky booking list --since now --until +7d\n\
+        arky booking list --upcoming --status confirmed\n\
+        arky booking list --past --since 30d\n\
+        arky booking list --account-id ACC_ID\n\
+        arky booking list --account-email jane@example.com")]
     List {
         #[arg(long)]
         query: Option<String>,
@@ -33,18 +43,22 @@ pub enum BookingCommand {
         service_id: Option<String>,
         #[arg(long)]
         provider_id: Option<String>,
-        #[arg(long)]
+        #[arg(long, conflicts_with = "account_email")]
         account_id: Option<String>,
-        #[arg(long, help = "Start timestamp (epoch seconds)")]
+        #[arg(long, help = "Filter by customer email (resolved to an account ID)")]
+        account_email: Option<String>,
+        #[arg(long, visible_alias = "since", help = "Start time: epoch, YYYY-MM-DD, now, 7d, +3d")]
         from: Option<String>,
-        #[arg(long, help = "End timestamp (epoch seconds)")]
+        #[arg(long, visible_alias = "until", help = "End time: epoch, YYYY-MM-DD, now, 7d, +3d")]
         to: Option<String>,
+        #[arg(long, conflicts_with_all = ["from", "past"], help = "Only bookings from now on")]
+        upcoming: bool,
+        #[arg(long, conflicts_with = "to", help = "Only bookings up to now")]
+        past: bool,
         #[arg(long, help = "Filter: pending, confirmed, cancelled, completed")]
         status: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
     },
     /// Create a booking directly (admin use)
     #[command(long_about = "Create a booking directly (bypasses checkout flow).\n\n\
@@ -78,6 +92,10 @@ pub enum BookingCommand {
     Create {
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Print only the created ID, e.g. for ID=$(arky ... --id-only)")]
+        id_only: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Update a booking
     #[command(long_about = "Update a booking (e.g., change status, reschedule).\n\n\
@@ -87,9 +105,12 @@ pub enum BookingCommand {
         arky booking update BOOKING_ID --data '{\"status\": \"cancelled\"}'")]
     Update {
         /// Booking ID
+        #[arg(required_unless_present = "example", default_value = "")]
         id: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Get a booking price quote
     #[command(long_about = "Calculate prices for a booking without creating it.\n\n\
@@ -112,10 +133,15 @@ pub enum BookingCommand {
             \"to\": 1771410600\n\
           }],\n\
           \"market\": \"us\"\n\
-        }'")]
+        }'\n\n\
+        Quote then checkout without retyping items:\n\
+        arky booking quote --save-last --data @slots.json\n\
+        arky booking checkout --use-last --data '{\"paymentMethodId\": \"pm_card_visa\"}'")]
     Quote {
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Save this quote to ~/.arky/cache/last-booking-quote.json for `checkout --use-last`")]
+        save_last: bool,
     },
     /// Checkout: create booking and process payment
     #[command(long_about = "Create a booking with payment in one step.\n\n\
@@ -141,14 +167,26 @@ pub enum BookingCommand {
             \"blocks\": []\n\
           }],\n\
           \"market\": \"us\"\n\
-        }'")]
+        }'\n\n\
+        Reusing the last saved quote (see `booking quote --save-last`):\n\
+        arky booking checkout --use-last --data '{\"paymentMethodId\": \"pm_card_visa\"}'\n\
+        --data fields are merged on top of the saved quote payload.")]
     Checkout {
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Start from the payload saved by `booking quote --save-last`")]
+        use_last: bool,
     },
 }
 
 pub async fn handle(cmd: BookingCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    if cmd.is_example() {
+        let example = match cmd {
+            BookingCommand::Create { .. } => create_example(),
+            _ => update_example(),
+        };
+        return print_example(&example);
+    }
     let biz_id = client.require_business_id()?;
 
     match cmd {
@@ -156,20 +194,24 @@ pub async fn handle(cmd: BookingCommand, client: &ArkyClient, format: &Format) -
             let result = client
                 .get(&format!("/v1/businesses/{biz_id}/bookings/{id}"), &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         BookingCommand::List {
             query,
             service_id,
             provider_id,
             account_id,
+            account_email,
             from,
             to,
+            upcoming,
+            past,
             status,
-            limit,
-            cursor,
+            list,
         } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+            let from = if upcoming { Some("now".to_string()) } else { from };
+            let to = if past { Some("now".to_string()) } else { to };
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
@@ -179,29 +221,34 @@ pub async fn handle(cmd: BookingCommand, client: &ArkyClient, format: &Format) -
             if let Some(ref p) = provider_id {
                 params.push(("providerIds", p.clone()));
             }
+            let account_id = match account_email {
+                Some(ref email) => Some(resolve_account_email(client, email).await?),
+                None => account_id,
+            };
             if let Some(ref a) = account_id {
                 params.push(("accountId", a.clone()));
             }
             if let Some(ref f) = from {
-                params.push(("from", f.clone()));
+                params.push(("from", parse_time_arg(f)?.to_string()));
             }
             if let Some(ref t) = to {
-                params.push(("to", t.clone()));
+                params.push(("to", parse_time_arg(t)?.to_string()));
             }
             if let Some(ref st) = status {
                 params.push(("status", st.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(&format!("/v1/businesses/{biz_id}/bookings"), &params_ref)
-                .await?;
-            crate::output::print_output(&result, format);
+            print_list(
+                client,
+                &format!("/v1/businesses/{biz_id}/bookings"),
+                &params_ref,
+                &list,
+                format,
+            )
+            .await?;
         }
-        BookingCommand::Create { data } => {
+        BookingCommand::Create { data, id_only, .. } => {
             let mut body = parse_data(data.as_deref())?;
             if body.get("market").is_none() {
                 body["market"] = json!("default");
@@ -209,18 +256,18 @@ pub async fn handle(cmd: BookingCommand, client: &ArkyClient, format: &Format) -
             let result = client
                 .post(&format!("/v1/businesses/{biz_id}/bookings"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            print_created(&result, "booking", id_only, format)?;
         }
-        BookingCommand::Update { id, data } => {
+        BookingCommand::Update { id, data, .. } => {
             let overlay = parse_data(data.as_deref())?;
             let mut body = json!({});
-            merge_data(&mut body, overlay);
+            merge_data(&mut body, overlay)?;
             let result = client
                 .put(&format!("/v1/businesses/{biz_id}/bookings/{id}"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
-        BookingCommand::Quote { data } => {
+        BookingCommand::Quote { data, save_last } => {
             let mut body = parse_data(data.as_deref())?;
             if body.get("market").is_none() {
                 body["market"] = json!("default");
@@ -228,10 +275,19 @@ pub async fn handle(cmd: BookingCommand, client: &ArkyClient, format: &Format) -
             let result = client
                 .post(&format!("/v1/businesses/{biz_id}/bookings/quote"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            if save_last {
+                save_last_quote("last-booking-quote", &body, &result)?;
+            }
+            crate::output::print_output(&result, format)?;
         }
-        BookingCommand::Checkout { data } => {
-            let mut body = parse_data(data.as_deref())?;
+        BookingCommand::Checkout { data, use_last } => {
+            let mut body = if use_last {
+                let mut saved = load_last_quote("last-booking-quote", "booking quote")?;
+                merge_data(&mut saved, parse_data(data.as_deref())?)?;
+                saved
+            } else {
+                parse_data(data.as_deref())?
+            };
             if body.get("market").is_none() {
                 body["market"] = json!("default");
             }
@@ -241,8 +297,27 @@ pub async fn handle(cmd: BookingCommand, client: &ArkyClient, format: &Format) -
                     &body,
                 )
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
     }
     Ok(())
 }
+
+/// `--example` skeleton for create
+fn create_example() -> Value {
+    json!({
+        "items": [{
+            "serviceId": "SVC_ID",
+            "providerId": "PROV_ID",
+            "from": 1771405200,
+            "to": 1771410600,
+            "blocks": []
+        }],
+        "market": "us"
+    })
+}
+
+/// `--example` skeleton for update
+fn update_example() -> Value {
+    json!({ "status": "confirmed" })
+}
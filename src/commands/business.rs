@@ -1,20 +1,46 @@
 use crate::client::ArkyClient;
-use crate::commands::{merge_data, parse_data};
-use crate::error::Result;
+use crate::commands::{
+    count_list, delete_entity, fetch_all, merge_data, parse_data, poll_until, print_created,
+    print_example, print_list, Example, ListArgs,
+};
+use crate::error::{CliError, Result};
 use crate::output::Format;
 use clap::Subcommand;
-use serde_json::json;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Subscription statuses `subscription --watch` stops on
+const SETTLED_SUBSCRIPTION_STATUSES: &[&str] = &["active", "canceled", "cancelled", "past_due"];
+
+/// Business lists counted by `get --expand counts` (also the `_counts` keys)
+const COUNTED_ENTITIES: &[&str] =
+    &["nodes", "products", "services", "providers", "orders", "bookings"];
 
 #[derive(Subcommand, Debug)]
 pub enum BusinessCommand {
     /// Get the current business details
     #[command(long_about = "Fetch the business identified by --business-id or ARKY_BUSINESS_ID.\n\n\
-        Example:\n\
-        arky business get\n\n\
+        --expand adds related data under underscored fields (comma-separated):\n\
+          counts        _counts: number of nodes, products, services, providers,\n\
+                        orders and bookings\n\
+          subscription  _subscription: the current subscription\n\
+          members       _members: accounts of the business\n\n\
+        Examples:\n\
+        arky business get\n\
+        arky business get --expand counts,subscription\n\n\
         Response shape:\n\
         {\"id\": \"biz_123\", \"key\": \"my-shop\", \"name\": \"My Shop\",\n\
-         \"blocks\": [...], \"status\": \"active\", \"createdAt\": \"...\"}")]
-    Get,
+         \"blocks\": [...], \"status\": \"active\", \"createdAt\": \"...\",\n\
+         \"_counts\": {\"nodes\": 12, \"products\": 4, ...}}")]
+    Get {
+        #[arg(
+            long,
+            value_delimiter = ',',
+            value_parser = ["counts", "subscription", "members"],
+            help = "Inline related data: counts, subscription, members"
+        )]
+        expand: Vec<String>,
+    },
     /// List all businesses
     #[command(long_about = "List businesses accessible to the current account.\n\n\
         Examples:\n\
@@ -26,10 +52,8 @@ pub enum BusinessCommand {
     List {
         #[arg(long)]
         query: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
     },
     /// Create a new business
     #[command(long_about = "Create a new business.\n\n\
@@ -67,10 +91,15 @@ pub enum BusinessCommand {
         }'")]
     Create {
         /// Business key (unique identifier)
+        #[arg(required_unless_present = "example", default_value = "")]
         key: String,
         /// JSON data for the business
         #[arg(long)]
         data: Option<String>,
+        #[arg(long, help = "Print only the created ID, e.g. for ID=$(arky ... --id-only)")]
+        id_only: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Update a business
     #[command(long_about = "Update a business by ID.\n\n\
@@ -78,15 +107,20 @@ pub enum BusinessCommand {
         arky business update BIZ_ID --data '{\"name\": \"New Name\"}'")]
     Update {
         /// Business ID
+        #[arg(required_unless_present = "example", default_value = "")]
         id: String,
         /// JSON data to update
         #[arg(long)]
         data: Option<String>,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Delete a business
     Delete {
         /// Business ID
         id: String,
+        #[arg(long, help = "Treat an already-deleted ID (404) as success")]
+        ignore_missing: bool,
     },
     /// Get parent businesses in hierarchy
     #[command(long_about = "Fetch parent businesses in the hierarchy.\n\n\
@@ -101,7 +135,23 @@ pub enum BusinessCommand {
     /// List available subscription plans
     Plans,
     /// Get current subscription details
-    Subscription,
+    #[command(long_about = "Fetch the business's current subscription.\n\n\
+        --watch polls until the status is active, canceled or past_due, e.g.\n\
+        right after paying in the checkout page from `arky business subscribe`.\n\
+        It stops after --watch-timeout seconds; Ctrl-C stops it early with exit\n\
+        code 130, printing the last status seen to stderr.\n\n\
+        Examples:\n\
+        arky business subscription\n\
+        arky business subscription --watch\n\
+        arky business subscription --watch --interval 10 --watch-timeout 900")]
+    Subscription {
+        #[arg(long, help = "Poll until the subscription is active, canceled or past_due")]
+        watch: bool,
+        #[arg(long, default_value_t = 5, requires = "watch", help = "Seconds between polls")]
+        interval: u64,
+        #[arg(long, default_value_t = 600, requires = "watch", help = "Seconds before giving up")]
+        watch_timeout: u64,
+    },
     /// Subscribe to a plan (creates Stripe checkout)
     #[command(long_about = "Subscribe the business to a plan.\n\n\
         Required (--data JSON):\n\
@@ -177,11 +227,15 @@ pub enum BusinessCommand {
         Required (--data JSON):\n\
           entity   Entity ID (order or booking ID)\n\
           amount   Refund amount in cents\n\n\
+        Before refunding, the order (or booking) is fetched and the amount is\n\
+        checked against what was paid minus earlier refunds. Use --force to skip.\n\n\
         Example:\n\
         arky business refund --data '{\"entity\": \"order_123\", \"amount\": 2999}'")]
     Refund {
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Skip the refundable-balance check")]
+        force: bool,
     },
     /// Connect an OAuth provider
     #[command(name = "oauth-connect", long_about = "Connect an OAuth provider to the business.\n\n\
@@ -205,56 +259,108 @@ pub enum BusinessCommand {
         #[arg(long)]
         provider: String,
     },
+    /// Manage webhooks stored in the business configs
+    Webhooks {
+        #[command(subcommand)]
+        cmd: WebhookCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WebhookCommand {
+    /// List configured webhooks
+    #[command(long_about = "List the webhooks in the business configs.webhooks array.\n\n\
+        Example:\n\
+        arky business webhooks list")]
+    List,
+    /// Add a webhook
+    #[command(long_about = "Add a webhook to configs.webhooks, keeping the rest of the business intact.\n\n\
+        Events are checked against `arky platform webhook-events` before saving.\n\n\
+        Required:\n\
+          --url      Endpoint that receives the events\n\
+          --events   Comma-separated event types\n\n\
+        Example:\n\
+        arky business webhooks add --url https://example.com/hook --events order.paid,booking.confirmed")]
+    Add {
+        #[arg(long)]
+        url: String,
+        #[arg(long, value_delimiter = ',', required = true)]
+        events: Vec<String>,
+    },
+    /// Remove a webhook by URL
+    #[command(long_about = "Remove the webhook with the given URL from configs.webhooks.\n\n\
+        Example:\n\
+        arky business webhooks remove --url https://example.com/hook")]
+    Remove {
+        #[arg(long)]
+        url: String,
+    },
 }
 
 pub async fn handle(cmd: BusinessCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    if cmd.is_example() {
+        return print_example(&example());
+    }
     match cmd {
-        BusinessCommand::Get => {
+        BusinessCommand::Get { expand } => {
             let biz_id = client.require_business_id()?;
-            let result = client.get(&format!("/v1/businesses/{biz_id}"), &[]).await?;
-            crate::output::print_output(&result, format);
+            let mut result = client.get(&format!("/v1/businesses/{biz_id}"), &[]).await?;
+            for part in &expand {
+                let (field, value) = match part.as_str() {
+                    "counts" => ("_counts", entity_counts(client, biz_id).await?),
+                    "subscription" => (
+                        "_subscription",
+                        client
+                            .get(&format!("/v1/businesses/{biz_id}/subscription"), &[])
+                            .await?,
+                    ),
+                    _ => {
+                        let params = [("businessId", biz_id)];
+                        let members = fetch_all(client, "/v1/accounts/search", &params).await?;
+                        ("_members", Value::Array(members))
+                    }
+                };
+                result[field] = value;
+            }
+            crate::output::print_output(&result, format)?;
         }
-        BusinessCommand::List {
-            query,
-            limit,
-            cursor,
-        } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+        BusinessCommand::List { query, list } => {
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client.get("/v1/businesses", &params_ref).await?;
-            crate::output::print_output(&result, format);
+            print_list(client, "/v1/businesses", &params_ref, &list, format).await?;
         }
-        BusinessCommand::Create { key, data } => {
+        BusinessCommand::Create {
+            key, data, id_only, ..
+        } => {
             let mut body = json!({ "key": key });
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
+            merge_data(&mut body, overlay)?;
             let result = client.post("/v1/businesses", &body).await?;
-            crate::output::print_output(&result, format);
+            print_created(&result, "business", id_only, format)?;
         }
-        BusinessCommand::Update { id, data } => {
+        BusinessCommand::Update { id, data, .. } => {
             let overlay = parse_data(data.as_deref())?;
             let mut body = json!({ "id": id });
-            merge_data(&mut body, overlay);
+            merge_data(&mut body, overlay)?;
             let result = client.put(&format!("/v1/businesses/{id}"), &body).await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
-        BusinessCommand::Delete { id } => {
-            let _ = client.delete(&format!("/v1/businesses/{id}")).await?;
-            crate::output::print_success("Business deleted");
+        BusinessCommand::Delete { id, ignore_missing } => {
+            let path = format!("/v1/businesses/{id}");
+            if delete_entity(client, &path, "business", ignore_missing).await? {
+                crate::output::print_success("Business deleted");
+            }
         }
         BusinessCommand::Parents => {
             let biz_id = client.require_business_id()?;
             let result = client
                 .get(&format!("/v1/businesses/{biz_id}/parents"), &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         BusinessCommand::TriggerBuilds => {
             let biz_id = client.require_business_id()?;
@@ -265,14 +371,28 @@ pub async fn handle(cmd: BusinessCommand, client: &ArkyClient, format: &Format)
         }
         BusinessCommand::Plans => {
             let result = client.get("/v1/businesses/plans", &[]).await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
-        BusinessCommand::Subscription => {
+        BusinessCommand::Subscription {
+            watch,
+            interval,
+            watch_timeout,
+        } => {
             let biz_id = client.require_business_id()?;
-            let result = client
-                .get(&format!("/v1/businesses/{biz_id}/subscription"), &[])
-                .await?;
-            crate::output::print_output(&result, format);
+            let path = format!("/v1/businesses/{biz_id}/subscription");
+            let result = if watch {
+                poll_until(
+                    "the subscription to settle",
+                    || client.get(&path, &[]),
+                    subscription_settled,
+                    Duration::from_secs(interval),
+                    Duration::from_secs(watch_timeout),
+                )
+                .await?
+            } else {
+                client.get(&path, &[]).await?
+            };
+            crate::output::print_output(&result, format)?;
         }
         BusinessCommand::Subscribe { data } => {
             let biz_id = client.require_business_id()?;
@@ -280,7 +400,7 @@ pub async fn handle(cmd: BusinessCommand, client: &ArkyClient, format: &Format)
             let result = client
                 .put(&format!("/v1/businesses/{biz_id}/subscribe"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         BusinessCommand::Portal { data } => {
             let biz_id = client.require_business_id()?;
@@ -291,7 +411,7 @@ pub async fn handle(cmd: BusinessCommand, client: &ArkyClient, format: &Format)
                     &body,
                 )
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         BusinessCommand::Invite { email, role } => {
             let biz_id = client.require_business_id()?;
@@ -319,7 +439,7 @@ pub async fn handle(cmd: BusinessCommand, client: &ArkyClient, format: &Format)
             let result = client
                 .put(&format!("/v1/businesses/{biz_id}/invitation"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         BusinessCommand::TestWebhook { data } => {
             let biz_id = client.require_business_id()?;
@@ -327,15 +447,18 @@ pub async fn handle(cmd: BusinessCommand, client: &ArkyClient, format: &Format)
             let result = client
                 .post(&format!("/v1/businesses/{biz_id}/webhooks/test"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
-        BusinessCommand::Refund { data } => {
+        BusinessCommand::Refund { data, force } => {
             let biz_id = client.require_business_id()?;
             let body = parse_data(data.as_deref())?;
+            if !force {
+                check_refund(client, biz_id, &body).await?;
+            }
             let result = client
                 .post(&format!("/v1/businesses/{biz_id}/refund"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         BusinessCommand::OauthConnect { data } => {
             let biz_id = client.require_business_id()?;
@@ -343,7 +466,7 @@ pub async fn handle(cmd: BusinessCommand, client: &ArkyClient, format: &Format)
             let result = client
                 .post(&format!("/v1/businesses/{biz_id}/oauth/connect"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         BusinessCommand::OauthDisconnect { provider } => {
             let biz_id = client.require_business_id()?;
@@ -354,8 +477,299 @@ pub async fn handle(cmd: BusinessCommand, client: &ArkyClient, format: &Format)
                     &body,
                 )
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
+        }
+        BusinessCommand::Webhooks { cmd } => handle_webhooks(cmd, client, format).await?,
+    }
+    Ok(())
+}
+
+fn example() -> Value {
+    json!({
+        "status": "active",
+        "timezone": "UTC",
+        "configs": {
+            "currencies": [{ "code": "usd", "symbol": "$", "decimals": 2 }],
+            "markets": [{
+                "id": "us",
+                "name": "United States",
+                "currencies": ["usd"],
+                "countries": ["US"],
+                "languages": ["en"],
+                "defaultLanguage": "en"
+            }],
+            "locations": [],
+            "paymentProviders": [],
+            "shippingProviders": [],
+            "emails": { "billing": "you@example.com", "support": "you@example.com" }
+        }
+    })
+}
+
+async fn handle_webhooks(cmd: WebhookCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    let biz_id = client.require_business_id()?;
+    let path = format!("/v1/businesses/{biz_id}");
+    let mut business = client.get(&path, &[]).await?;
+    let mut webhooks = business["configs"]["webhooks"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+
+    match cmd {
+        WebhookCommand::List => {
+            crate::output::print_output(&Value::Array(webhooks), format)?;
+            return Ok(());
+        }
+        WebhookCommand::Add { url, events } => {
+            if webhooks.iter().any(|w| w["url"] == url.as_str()) {
+                return Err(CliError::InvalidInput(format!(
+                    "A webhook for {url} already exists. Remove it first to change its events"
+                )));
+            }
+            let available = client.get("/v1/platform/events", &[]).await?;
+            let valid: Vec<&str> = available
+                .as_array()
+                .map(|a| a.iter().filter_map(|e| e.as_str()).collect())
+                .unwrap_or_default();
+            let unknown: Vec<&str> = events
+                .iter()
+                .map(|e| e.as_str())
+                .filter(|e| !valid.contains(e))
+                .collect();
+            if !valid.is_empty() && !unknown.is_empty() {
+                return Err(CliError::InvalidInput(format!(
+                    "Unknown webhook events: {}. Valid events: {}",
+                    unknown.join(", "),
+                    valid.join(", ")
+                )));
+            }
+            webhooks.push(json!({ "url": url, "events": events }));
+        }
+        WebhookCommand::Remove { url } => {
+            let before = webhooks.len();
+            webhooks.retain(|w| w["url"] != url.as_str());
+            if webhooks.len() == before {
+                return Err(CliError::InvalidInput(format!("No webhook configured for {url}")));
+            }
+        }
+    }
+
+    business["configs"]["webhooks"] = Value::Array(webhooks);
+    let result = client.put(&path, &business).await?;
+    crate::output::print_output(&result, format)?;
+    Ok(())
+}
+
+/// Business configs fetched at most once per command invocation, used to
+/// check market/currency references locally before hitting the API.
+pub struct BusinessConfigs<'a> {
+    client: &'a ArkyClient,
+    configs: Option<Value>,
+}
+
+impl<'a> BusinessConfigs<'a> {
+    pub fn new(client: &'a ArkyClient) -> Self {
+        Self {
+            client,
+            configs: None,
+        }
+    }
+
+    pub async fn get(&mut self) -> Result<&Value> {
+        if self.configs.is_none() {
+            let biz_id = self.client.require_business_id()?;
+            let business = self
+                .client
+                .get(&format!("/v1/businesses/{biz_id}"), &[])
+                .await?;
+            self.configs = Some(business["configs"].clone());
+        }
+        Ok(self.configs.get_or_insert(Value::Null))
+    }
+
+    pub async fn check_market(&mut self, market: &str) -> Result<()> {
+        let valid = market_ids(self.get().await?);
+        check_reference("market", market, &valid)
+    }
+
+    pub async fn check_currency(&mut self, currency: &str) -> Result<()> {
+        let valid = currency_codes(self.get().await?);
+        check_reference("currency", currency, &valid)
+    }
+
+    /// Check every `{market, currency}` entry in a prices array
+    pub async fn check_prices(&mut self, prices: &Value) -> Result<()> {
+        for price in prices.as_array().into_iter().flatten() {
+            if let Some(market) = price["market"].as_str() {
+                self.check_market(market).await?;
+            }
+            if let Some(currency) = price["currency"].as_str() {
+                self.check_currency(currency).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn market_ids(configs: &Value) -> Vec<String> {
+    configs["markets"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|m| m["id"].as_str().or_else(|| m["key"].as_str()))
+        .map(String::from)
+        .collect()
+}
+
+fn currency_codes(configs: &Value) -> Vec<String> {
+    configs["currencies"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|c| c["code"].as_str().or_else(|| c.as_str()))
+        .map(String::from)
+        .collect()
+}
+
+/// Codes are compared case-insensitively ("usd" and "USD" are both used).
+/// An empty list means the configs couldn't be read, so nothing is checked.
+fn check_reference(kind: &str, value: &str, valid: &[String]) -> Result<()> {
+    if valid.is_empty() || valid.iter().any(|v| v.eq_ignore_ascii_case(value)) {
+        return Ok(());
+    }
+    Err(CliError::InvalidInput(format!(
+        "Unknown {kind} '{value}'. Valid values from business configs: {}",
+        valid.join(", ")
+    )))
+}
+
+/// Item counts of the business's main lists, keyed by entity
+async fn entity_counts(client: &ArkyClient, biz_id: &str) -> Result<Value> {
+    let mut counts = serde_json::Map::new();
+    for entity in COUNTED_ENTITIES {
+        let path = format!("/v1/businesses/{biz_id}/{entity}");
+        let count = count_list(client, &path, &[], 100).await?;
+        counts.insert(entity.to_string(), json!(count));
+    }
+    Ok(Value::Object(counts))
+}
+
+/// Whether the subscription (bare or under `data`) has reached a settled status
+fn subscription_settled(sub: &Value) -> bool {
+    sub["status"]
+        .as_str()
+        .or_else(|| sub["data"]["status"].as_str())
+        .is_some_and(|s| SETTLED_SUBSCRIPTION_STATUSES.contains(&s))
+}
+
+/// Fetch the refund target (order, else booking) and reject amounts above its
+/// refundable balance. Entities without a readable paid total are let through.
+async fn check_refund(client: &ArkyClient, biz_id: &str, body: &Value) -> Result<()> {
+    let (Some(entity_id), Some(amount)) = (body["entity"].as_str(), body["amount"].as_i64()) else {
+        return Ok(());
+    };
+    let entity = match client
+        .get(&format!("/v1/businesses/{biz_id}/orders/{entity_id}"), &[])
+        .await
+    {
+        Err(CliError::Api { status: 404, .. }) => {
+            client
+                .get(&format!("/v1/businesses/{biz_id}/bookings/{entity_id}"), &[])
+                .await?
         }
+        other => other?,
+    };
+    let Some(refundable) = refundable_balance(&entity) else {
+        crate::output::print_warning(&format!(
+            "Could not read the paid total of {entity_id}; refunding without a balance check"
+        ));
+        return Ok(());
+    };
+    if amount > refundable {
+        return Err(CliError::InvalidInput(format!(
+            "Refund of {amount} exceeds the refundable balance of {refundable} for {entity_id}. \
+             Use --force to send it anyway"
+        )));
     }
     Ok(())
 }
+
+/// Paid total minus what was already refunded, in minor units. Refunds are read
+/// from `payment.refunds[].amount`, else `payment.refunded`, else refund events.
+fn refundable_balance(entity: &Value) -> Option<i64> {
+    let payment = &entity["payment"];
+    let paid = payment["total"].as_i64().or_else(|| entity["total"].as_i64())?;
+    let sum = |items: &Value, amount: fn(&Value) -> Option<i64>| -> Option<i64> {
+        items.as_array().map(|a| a.iter().filter_map(amount).sum())
+    };
+    let refunded = sum(&payment["refunds"], |r| r["amount"].as_i64())
+        .or_else(|| payment["refunded"].as_i64())
+        .or_else(|| {
+            sum(&entity["events"], |e| {
+                let is_refund = e["type"].as_str().is_some_and(|t| t.contains("refund"));
+                is_refund
+                    .then(|| e["amount"].as_i64().or_else(|| e["data"]["amount"].as_i64()))
+                    .flatten()
+            })
+        })
+        .unwrap_or(0);
+    Some((paid - refunded).max(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_settled() {
+        assert!(subscription_settled(&json!({"status": "active"})));
+        assert!(subscription_settled(&json!({"data": {"status": "past_due"}})));
+        assert!(!subscription_settled(&json!({"status": "incomplete"})));
+        assert!(!subscription_settled(&json!(null)));
+    }
+
+    #[test]
+    fn test_refundable_balance() {
+        let order = json!({
+            "payment": {"total": 5000, "refunds": [{"amount": 1000}, {"amount": 500}]}
+        });
+        assert_eq!(refundable_balance(&order), Some(3500));
+
+        let booking = json!({"payment": {"total": 2000, "refunded": 2000}});
+        assert_eq!(refundable_balance(&booking), Some(0));
+
+        let with_events = json!({
+            "total": 3000,
+            "events": [
+                {"type": "order.refunded", "data": {"amount": 1200}},
+                {"type": "order.paid", "data": {"amount": 3000}}
+            ]
+        });
+        assert_eq!(refundable_balance(&with_events), Some(1800));
+
+        assert_eq!(refundable_balance(&json!({"status": "pending"})), None);
+    }
+
+    fn configs() -> Value {
+        json!({
+            "currencies": [{"code": "usd", "symbol": "$"}, {"code": "eur", "symbol": "€"}],
+            "markets": [{"id": "us", "currencies": ["usd"]}, {"id": "eu", "currencies": ["eur"]}]
+        })
+    }
+
+    #[test]
+    fn test_market_and_currency_lookup() {
+        assert_eq!(market_ids(&configs()), vec!["us", "eu"]);
+        assert_eq!(currency_codes(&configs()), vec!["usd", "eur"]);
+        assert!(market_ids(&Value::Null).is_empty());
+    }
+
+    #[test]
+    fn test_check_reference() {
+        let valid = currency_codes(&configs());
+        assert!(check_reference("currency", "USD", &valid).is_ok());
+        let err = check_reference("currency", "gbp", &valid).unwrap_err();
+        assert!(err.to_string().contains("usd, eur"));
+        assert!(check_reference("market", "anything", &[]).is_ok());
+    }
+}
@@ -1,22 +1,34 @@
-use crate::config::{Config, ResolvedConfig};
+use crate::commands::write_out;
+use crate::config::{Config, ResolvedConfig, SETTINGS};
 use crate::error::{CliError, Result};
 use crate::output::Format;
 use clap::Subcommand;
+use serde_json::Value;
 
 #[derive(Subcommand, Debug)]
 pub enum ConfigCommand {
     /// Show the current resolved configuration
     #[command(long_about = "Display the current configuration with resolved values.\n\n\
-        Shows values from all sources (CLI flags > env vars > config file).\n\
-        Token is partially masked for security.\n\n\
+        Shows values from all sources (CLI flags > env vars > --profile file >\n\
+        config file > default), key by key. The config file is the --config file,\n\
+        else ~/.arky/config.json. Blank values (e.g. --business-id \"\") count as\n\
+        unset and don't override a lower level. Token is partially masked for security.\n\n\
+        --explain shows where each value came from: flag, env, profile,\n\
+        config file or default:\n\
+        [{\"key\": \"format\", \"value\": \"table\", \"source\": \"env ARKY_FORMAT\"}, ...]\n\n\
         Example:\n\
-        arky config show")]
-    Show,
-    /// Set a config value (base_url, business_id, token, format)
+        arky config show\n\
+        arky config show --explain")]
+    Show {
+        #[arg(long, help = "Show which flag, variable or file each value came from")]
+        explain: bool,
+    },
+    /// Set a config value (base_url, business_id, business_key, token, format)
     #[command(long_about = "Persist a configuration value to ~/.arky/config.json.\n\n\
         Valid keys:\n\
           base_url      Server URL (e.g., http://localhost:8000)\n\
           business_id   Default business ID for all commands\n\
+          business_key  Business key (e.g. my-shop), resolved to an ID when business_id is unset\n\
           token         Auth token (usually set via `arky auth verify`)\n\
           format        Default output format: json, table, plain\n\n\
         Examples:\n\
@@ -31,18 +43,54 @@ pub enum ConfigCommand {
     },
     /// Show the config file path
     #[command(long_about = "Print the path to the config file.\n\n\
-        Default: ~/.arky/config.json\n\n\
+        Default: ~/.arky/config.json; --config PATH (or ARKY_CONFIG) and\n\
+        --profile NAME select another file.\n\n\
         Example:\n\
-        arky config path")]
+        arky config path\n\
+        arky --config ./arky.json config path")]
     Path,
+    /// Rewrite the config file in the current schema
+    #[command(long_about = "Upgrade ~/.arky/config.json to the current schema and report what changed.\n\n\
+        Legacy camelCase keys (baseUrl, businessId, refreshToken, ...) are renamed,\n\
+        unknown keys and non-string values are dropped. Older files are already\n\
+        read this way on every run; this command persists the result.\n\n\
+        Example:\n\
+        arky config migrate\n\n\
+        Response: {\"path\": \"...\", \"changes\": [\"renamed 'baseUrl' to 'base_url'\", ...]}")]
+    Migrate,
+    /// Export the config file, e.g. to set up another machine
+    #[command(long_about = "Print the config file as JSON, or write it to --out.\n\n\
+        The token and refresh_token are left out unless --include-secrets is\n\
+        given. An export with secrets logs in as you: keep it private and delete\n\
+        it once imported. With --profile, the profile's file is exported.\n\n\
+        Examples:\n\
+        arky config export --out arky-config.json\n\
+        arky config export --include-secrets > arky-config.json")]
+    Export {
+        #[arg(long, value_name = "FILE", help = "Write to FILE instead of stdout")]
+        out: Option<String>,
+        #[arg(long, help = "Include the token and refresh_token")]
+        include_secrets: bool,
+    },
+    /// Merge an exported config into the local config
+    #[command(long_about = "Merge a file from `arky config export` into the local config.\n\n\
+        Keys set in the file replace local values; keys it doesn't set are kept.\n\
+        Older key spellings are upgraded as in `arky config migrate`.\n\n\
+        Example:\n\
+        arky config import arky-config.json")]
+    Import {
+        /// File written by `arky config export`
+        file: String,
+    },
 }
 
 pub async fn handle(cmd: ConfigCommand, resolved: &ResolvedConfig, format: &Format) -> Result<()> {
     match cmd {
-        ConfigCommand::Show => {
-            let display = serde_json::json!({
+        ConfigCommand::Show { explain } => {
+            let mut display = serde_json::json!({
                 "base_url": resolved.base_url,
                 "business_id": resolved.business_id,
+                "business_key": resolved.business_key,
                 "token": resolved.token.as_ref().map(|t| {
                     if t.len() > 20 {
                         format!("{}...{}", &t[..10], &t[t.len()-6..])
@@ -53,18 +101,32 @@ pub async fn handle(cmd: ConfigCommand, resolved: &ResolvedConfig, format: &Form
                 "format": resolved.format,
                 "config_file": Config::config_path().to_string_lossy().to_string(),
             });
-            crate::output::print_output(&display, format);
+            if explain {
+                let rows: Vec<Value> = SETTINGS
+                    .iter()
+                    .map(|setting| {
+                        serde_json::json!({
+                            "key": setting.key,
+                            "value": display[setting.key].take(),
+                            "source": setting.describe(resolved.source(setting.key)),
+                        })
+                    })
+                    .collect();
+                display = Value::Array(rows);
+            }
+            crate::output::print_output(&display, format)?;
         }
         ConfigCommand::Set { key, value } => {
             let mut cfg = Config::load_file();
             match key.as_str() {
                 "base_url" | "base-url" => cfg.base_url = Some(value),
                 "business_id" | "business-id" => cfg.business_id = Some(value),
+                "business_key" | "business-key" => cfg.business_key = Some(value),
                 "token" => cfg.token = Some(value),
                 "format" => cfg.format = Some(value),
                 _ => {
                     return Err(CliError::InvalidInput(format!(
-                        "Unknown config key: {key}. Valid keys: base_url, business_id, token, format"
+                        "Unknown config key: {key}. Valid keys: base_url, business_id, business_key, token, format"
                     )));
                 }
             }
@@ -74,6 +136,117 @@ pub async fn handle(cmd: ConfigCommand, resolved: &ResolvedConfig, format: &Form
         ConfigCommand::Path => {
             println!("{}", Config::config_path().to_string_lossy());
         }
+        ConfigCommand::Migrate => {
+            let path = Config::config_path();
+            let Some(raw) = Config::load_raw()? else {
+                crate::output::print_success(&format!(
+                    "No config file at {}, nothing to migrate",
+                    path.display()
+                ));
+                return Ok(());
+            };
+            let (cfg, changes) = Config::migrate(raw);
+            if changes.is_empty() {
+                crate::output::print_success("Config is already up to date");
+                return Ok(());
+            }
+            cfg.save_file()?;
+            let report = serde_json::json!({
+                "path": path.to_string_lossy(),
+                "changes": changes,
+            });
+            crate::output::print_output(&report, format)?;
+        }
+        ConfigCommand::Export {
+            out,
+            include_secrets,
+        } => {
+            let cfg = Config::load_file();
+            let exported = if include_secrets {
+                cfg.clone()
+            } else {
+                cfg.without_secrets()
+            };
+            let content = serde_json::to_string_pretty(&exported)? + "\n";
+            match out.as_deref().filter(|o| *o != "-") {
+                Some(path) if include_secrets => write_private(path, content.as_bytes())?,
+                _ => write_out(out.as_deref().unwrap_or("-"), content.as_bytes())?,
+            }
+            if include_secrets && cfg.has_secrets() {
+                crate::output::print_warning(
+                    "THIS EXPORT CONTAINS YOUR AUTH TOKEN. Anyone with it can act as you: \
+                     keep it private and delete it after importing",
+                );
+            } else if cfg.has_secrets() {
+                crate::output::print_warning(
+                    "Token left out; pass --include-secrets to export it too",
+                );
+            }
+            if let Some(out) = out.filter(|o| o != "-") {
+                crate::output::print_success(&format!("Config exported to {out}"));
+            }
+        }
+        ConfigCommand::Import { file } => {
+            let content = std::fs::read_to_string(&file)
+                .map_err(|e| CliError::InvalidInput(format!("Failed to read {file}: {e}")))?;
+            let raw: Value = serde_json::from_str(&content)
+                .map_err(|e| CliError::InvalidInput(format!("Invalid JSON in {file}: {e}")))?;
+            let (imported, changes) = Config::migrate(raw);
+            for change in &changes {
+                crate::output::print_warning(&format!("{file}: {change}"));
+            }
+            let mut cfg = Config::load_file();
+            let keys = cfg.merge(imported);
+            if keys.is_empty() {
+                crate::output::print_success(&format!("Nothing to import from {file}"));
+                return Ok(());
+            }
+            cfg.save_file()?;
+            crate::output::print_success(&format!(
+                "Imported {} into {}",
+                keys.join(", "),
+                Config::config_path().display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Write a file only the owner can read (0600 on Unix), also when it already
+/// existed with wider permissions, since it holds the auth token
+fn write_private(path: &str, bytes: &[u8]) -> Result<()> {
+    use std::io::Write;
+    let failed = |e: std::io::Error| CliError::InvalidInput(format!("Failed to write {path}: {e}"));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path).map_err(failed)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600)).map_err(failed)?;
     }
+    file.write_all(bytes).map_err(failed)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private_restricts_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("export.json");
+        std::fs::write(&path, "old contents that are longer").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(path.to_str().unwrap(), b"{}").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+    }
+}
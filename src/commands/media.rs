@@ -1,9 +1,13 @@
 use crate::client::ArkyClient;
-use crate::commands::{merge_data, parse_data};
+use crate::commands::{
+    confirm_delete, delete_entity, delete_from_stdin, fetch_all, fetch_all_concurrent,
+    get_concurrent, merge_data, parse_data, print_list, DeleteArgs, ListArgs,
+};
 use crate::error::{CliError, Result};
 use crate::output::Format;
 use clap::Subcommand;
-use serde_json::json;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Subcommand, Debug)]
 pub enum MediaCommand {
@@ -12,21 +16,39 @@ pub enum MediaCommand {
         Accepts one or more file paths. Files are uploaded as multipart form data.\n\
         Returns an array of created media objects with IDs and URLs.\n\n\
         Supported: images (png, jpg, gif, webp, svg), video, PDF, any file type.\n\
-        Max total request size: 50MB.\n\n\
+        Max total request size: 50MB (checked before sending).\n\
+        Uploads time out after 300s; raise with --upload-timeout <secs>.\n\n\
         Examples:\n\
         arky media upload photo.jpg\n\
         arky media upload hero.png logo.svg banner.webp\n\
-        arky media upload /path/to/document.pdf\n\n\
+        arky media upload /path/to/document.pdf\n\
+        arky media upload hero.png --title \"Hero banner\" --alt \"Team at the office\"\n\n\
+        Metadata:\n\
+          --title and --alt are sent as titles[i] / alts[i] form fields next to\n\
+          files[i]. Give each once to apply it to every file, or once per file\n\
+          (matched in order). Without --title the file name is used.\n\n\
         Response shape:\n\
         [{\"id\": \"media_abc\", \"mimeType\": \"image/png\", \"title\": \"photo.png\",\n\
           \"resolutions\": {\"original\": {\"url\": \"https://...\"}},\n\
           \"businessId\": \"biz_123\", \"uploadedAt\": \"2025-01-01T00:00:00Z\"}]\n\n\
         Use the returned media ID in relationship_media blocks:\n\
-        {\"key\": \"image\", \"type\": \"relationship_media\", \"value\": {\"id\": \"media_abc\"}}")]
+        {\"key\": \"image\", \"type\": \"relationship_media\", \"value\": {\"id\": \"media_abc\"}}\n\n\
+        Upload and attach to a node in one step (single file only):\n\
+        arky media upload hero.png --attach-to-node NODE_ID --block-key hero\n\
+        The node's relationship_media block with that key is set to the new media\n\
+        (added if missing) and the full node is PUT back. Prints {\"media\", \"node\"}.")]
     Upload {
         /// File paths to upload
         #[arg(required = true)]
         files: Vec<String>,
+        #[arg(long, requires = "block_key", help = "Node ID to attach the uploaded media to")]
+        attach_to_node: Option<String>,
+        #[arg(long, requires = "attach_to_node", help = "Key of the relationship_media block to set")]
+        block_key: Option<String>,
+        #[arg(long, help = "Media title: once for all files, or once per file")]
+        title: Vec<String>,
+        #[arg(long, help = "Alt text: once for all files, or once per file")]
+        alt: Vec<String>,
     },
     /// List media files
     #[command(long_about = "List media files in the business library.\n\n\
@@ -35,15 +57,18 @@ pub enum MediaCommand {
         arky media list\n\
         arky media list --limit 5\n\
         arky media list --mime-type image/png\n\
-        arky media list --query \"logo\" --sort-field uploadedAt --sort-direction desc\n\n\
+        arky media list --query \"logo\" --sort-field uploadedAt --sort-direction desc\n\
+        arky media list --unused\n\n\
+        --unused fetches every media file plus all nodes, products, services and\n\
+        providers, and keeps media that no relationship_media block points at.\n\
+        This is a best-effort, client-side check: references from anything else\n\
+        (orders, workflows, external sites) are not seen, so review before deleting.\n\n\
         Response shape:\n\
         {\"data\": [{\"id\": \"...\", \"mimeType\": \"image/png\", \"title\": \"...\",\n\
           \"resolutions\": {\"original\": {\"url\": \"...\"}}}], \"cursor\": \"...\"}")]
     List {
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
         #[arg(long)]
         query: Option<String>,
         #[arg(long, help = "Filter by MIME type (e.g., image/png, video/mp4)")]
@@ -52,6 +77,8 @@ pub enum MediaCommand {
         sort_field: Option<String>,
         #[arg(long)]
         sort_direction: Option<String>,
+        #[arg(long, help = "Only media not referenced by any node, product, service or provider")]
+        unused: bool,
     },
     /// Get a media file by ID
     #[command(long_about = "Fetch a single media file by ID.\n\n\
@@ -78,9 +105,21 @@ pub enum MediaCommand {
         data: Option<String>,
     },
     /// Delete a media file
+    #[command(long_about = "Delete a media file by ID.\n\n\
+        --show fetches it first, prints its id/key/status/title and asks before\n\
+        deleting; add --yes to skip the question.\n\n\
+        --stdin deletes every ID piped in (one per line, or a JSON array of IDs or\n\
+        objects with an id), reporting each and continuing past failures unless\n\
+        --stop-on-error is given.\n\n\
+        Examples:\n\
+        arky media delete MEDIA_ID\n\
+        arky media list --all --pointer /data | arky media delete --stdin")]
     Delete {
         /// Media ID
+        #[arg(required_unless_present = "stdin", default_value = "")]
         id: String,
+        #[command(flatten)]
+        delete: DeleteArgs,
     },
 }
 
@@ -88,7 +127,21 @@ pub async fn handle(cmd: MediaCommand, client: &ArkyClient, format: &Format) ->
     let biz_id = client.require_business_id()?;
 
     match cmd {
-        MediaCommand::Upload { files } => {
+        MediaCommand::Upload {
+            files,
+            attach_to_node,
+            block_key,
+            title,
+            alt,
+        } => {
+            if attach_to_node.is_some() && files.len() != 1 {
+                return Err(CliError::InvalidInput(
+                    "--attach-to-node works with a single file only".into(),
+                ));
+            }
+            let mut fields = per_file_fields("titles", "--title", &title, files.len())?;
+            fields.extend(per_file_fields("alts", "--alt", &alt, files.len())?);
+
             let mut file_data: Vec<(String, Vec<u8>, String)> = Vec::new();
 
             for path_str in &files {
@@ -110,22 +163,49 @@ pub async fn handle(cmd: MediaCommand, client: &ArkyClient, format: &Format) ->
             }
 
             let result = client
-                .upload(&format!("/v1/businesses/{biz_id}/media"), file_data)
+                .upload(&format!("/v1/businesses/{biz_id}/media"), file_data, fields)
                 .await?;
-            crate::output::print_output(&result, format);
+
+            let (Some(node_id), Some(block_key)) = (attach_to_node, block_key) else {
+                crate::output::print_output(&result, format)?;
+                return Ok(());
+            };
+
+            let media = result
+                .as_array()
+                .or_else(|| result["data"].as_array())
+                .and_then(|items| items.first())
+                .unwrap_or(&result)
+                .clone();
+            // The upload already happened; keep its result reachable if attaching fails
+            let media_id = media["id"].as_str().ok_or_else(|| {
+                CliError::InvalidInput(format!(
+                    "Upload response did not include a media ID; nothing was attached: {result}"
+                ))
+            })?;
+
+            let node_path = format!("/v1/businesses/{biz_id}/nodes/{node_id}");
+            let attached = async {
+                let mut node = client.get(&node_path, &[]).await?;
+                attach_media_block(&mut node, &block_key, media_id)?;
+                client.put(&node_path, &node).await
+            };
+            let node = attached.await.map_err(|e| {
+                CliError::InvalidInput(format!(
+                    "Uploaded media {media_id}, but attaching it to node {node_id} failed: {e}"
+                ))
+            })?;
+            crate::output::print_output(&json!({ "media": media, "node": node }), format)?;
         }
         MediaCommand::List {
-            limit,
-            cursor,
+            list,
             query,
             mime_type,
             sort_field,
             sort_direction,
+            unused,
         } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
@@ -140,36 +220,197 @@ pub async fn handle(cmd: MediaCommand, client: &ArkyClient, format: &Format) ->
             }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(&format!("/v1/businesses/{biz_id}/media"), &params_ref)
-                .await?;
-            crate::output::print_output(&result, format);
+            if unused {
+                let media_path = format!("/v1/businesses/{biz_id}/media");
+                let media = fetch_all(client, &media_path, &params_ref).await?;
+                let entity_paths: Vec<String> = ["nodes", "products", "services", "providers"]
+                    .iter()
+                    .map(|kind| format!("/v1/businesses/{biz_id}/{kind}"))
+                    .collect();
+                let mut referenced = HashSet::new();
+                let entities = fetch_all_concurrent(client, &entity_paths, list.concurrency);
+                for entity in entities.await?.iter().flatten() {
+                    collect_media_refs(entity, &mut referenced);
+                }
+                let unused: Vec<Value> = media
+                    .into_iter()
+                    .filter(|m| m["id"].as_str().is_some_and(|id| !referenced.contains(id)))
+                    .collect();
+                let result = if list.count {
+                    json!(unused.len())
+                } else {
+                    json!({ "data": unused })
+                };
+                crate::output::print_output(&result, format)?;
+                return Ok(());
+            }
+            print_list(
+                client,
+                &format!("/v1/businesses/{biz_id}/media"),
+                &params_ref,
+                &list,
+                format,
+            )
+            .await?;
         }
         MediaCommand::Get { id } => {
             let result = client
                 .get(&format!("/v1/businesses/{biz_id}/media/{id}"), &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         MediaCommand::Update { id, data } => {
             let mut body = json!({});
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
+            merge_data(&mut body, overlay)?;
             let result = client
                 .put(&format!("/v1/businesses/{biz_id}/media/{id}"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
-        MediaCommand::Delete { id } => {
-            let _ = client
-                .delete(&format!("/v1/businesses/{biz_id}/media/{id}"))
-                .await?;
-            crate::output::print_success("Media deleted");
+        MediaCommand::Delete { id, delete } => {
+            let collection = format!("/v1/businesses/{biz_id}/media");
+            if delete.stdin {
+                return delete_from_stdin(client, &collection, "media", &delete).await;
+            }
+            let path = format!("{collection}/{id}");
+            if !confirm_delete(client, &path, "media", &delete, format).await? {
+                return Ok(());
+            }
+            if delete_entity(client, &path, "media", delete.ignore_missing).await? {
+                crate::output::print_success("Media deleted");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `{name}[i]` form fields for a repeatable per-file flag: one value applies
+/// to every file, otherwise there must be exactly one value per file
+fn per_file_fields(
+    name: &str,
+    flag: &str,
+    values: &[String],
+    files: usize,
+) -> Result<Vec<(String, String)>> {
+    match values.len() {
+        0 => Ok(Vec::new()),
+        n if n == 1 || n == files => Ok((0..files)
+            .map(|i| (format!("{name}[{i}]"), values[i.min(n - 1)].clone()))
+            .collect()),
+        n => Err(CliError::InvalidInput(format!(
+            "Got {n} {flag} values for {files} files. Give one for all files or one per file"
+        ))),
+    }
+}
+
+/// Point the node's relationship_media block `key` at the media, adding the block if missing
+fn attach_media_block(node: &mut Value, key: &str, media_id: &str) -> Result<()> {
+    let value = json!(format!("media:{media_id}"));
+    if !node["blocks"].is_array() {
+        node["blocks"] = json!([]);
+    }
+    let blocks = node["blocks"].as_array_mut().expect("blocks is an array");
+
+    match blocks.iter_mut().find(|b| b["key"] == key) {
+        Some(block) if block["type"] == "relationship_media" => block["value"] = value,
+        Some(block) => {
+            return Err(CliError::InvalidInput(format!(
+                "Block '{key}' has type {}, expected relationship_media",
+                block["type"]
+            )));
         }
+        None => blocks.push(json!({
+            "type": "relationship_media",
+            "id": uuid::Uuid::new_v4().to_string(),
+            "key": key,
+            "properties": {},
+            "value": value,
+        })),
     }
     Ok(())
 }
 
+/// `node get --resolve-media`: add `mediaUrls` (media ID -> original URL) to
+/// every relationship_media block in `node`, fetching each media once.
+/// Media that no longer exist map to null.
+pub async fn resolve_media_urls(client: &ArkyClient, node: &mut Value) -> Result<()> {
+    let biz_id = client.require_business_id()?;
+    let mut refs = HashSet::new();
+    collect_media_refs(node, &mut refs);
+    let ids: Vec<String> = refs.into_iter().collect();
+    let paths: Vec<String> =
+        ids.iter().map(|id| format!("/v1/businesses/{biz_id}/media/{id}")).collect();
+    let mut urls = HashMap::new();
+    for (id, media) in ids.into_iter().zip(get_concurrent(client, &paths).await?) {
+        let url = match media {
+            Ok(media) => media["resolutions"]["original"]["url"].clone(),
+            Err(CliError::Api { status: 404, .. }) => {
+                crate::output::print_warning(&format!("Media {id} not found"));
+                Value::Null
+            }
+            Err(e) => return Err(e),
+        };
+        urls.insert(id, url);
+    }
+    inline_media_urls(node, &urls);
+    Ok(())
+}
+
+fn inline_media_urls(value: &mut Value, urls: &HashMap<String, Value>) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(|v| inline_media_urls(v, urls)),
+        Value::Object(obj) => {
+            obj.values_mut().for_each(|v| inline_media_urls(v, urls));
+            if obj.get("type").and_then(Value::as_str) == Some("relationship_media") {
+                let mut ids = HashSet::new();
+                if let Some(v) = obj.get("value") {
+                    media_ids(v, &mut ids);
+                }
+                let block_urls = ids
+                    .into_iter()
+                    .filter_map(|id| urls.get(&id).map(|url| (id, url.clone())))
+                    .collect();
+                obj.insert("mediaUrls".to_string(), Value::Object(block_urls));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collect media IDs referenced by relationship_media blocks anywhere in
+/// `value` (nested blocks included). Values may be "media:<id>", a bare id,
+/// `{"id": ...}`, or an array of those.
+fn collect_media_refs(value: &Value, refs: &mut HashSet<String>) {
+    match value {
+        Value::Array(items) => items.iter().for_each(|v| collect_media_refs(v, refs)),
+        Value::Object(obj) => {
+            if obj.get("type").and_then(Value::as_str) == Some("relationship_media") {
+                if let Some(v) = obj.get("value") {
+                    media_ids(v, refs);
+                }
+            }
+            obj.values().for_each(|v| collect_media_refs(v, refs));
+        }
+        _ => {}
+    }
+}
+
+fn media_ids(value: &Value, refs: &mut HashSet<String>) {
+    match value {
+        Value::String(s) => {
+            refs.insert(s.strip_prefix("media:").unwrap_or(s).to_string());
+        }
+        Value::Object(obj) => {
+            if let Some(id) = obj.get("id").and_then(Value::as_str) {
+                refs.insert(id.strip_prefix("media:").unwrap_or(id).to_string());
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|v| media_ids(v, refs)),
+        _ => {}
+    }
+}
+
 fn mime_from_ext(ext: Option<&str>) -> String {
     match ext.map(|e| e.to_lowercase()).as_deref() {
         Some("png") => "image/png",
@@ -195,3 +436,84 @@ fn mime_from_ext(ext: Option<&str>) -> String {
     }
     .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_media_refs() {
+        let node = json!({
+            "id": "n1",
+            "blocks": [
+                {"type": "relationship_media", "key": "hero", "value": "media:m1"},
+                {"type": "relationship_media", "key": "logo", "value": {"id": "m2"}},
+                {"type": "list", "key": "gallery", "value": [
+                    {"type": "relationship_media", "key": "img", "value": ["media:m3"]},
+                ]},
+                {"type": "text", "key": "note", "value": "media:not-a-ref"},
+            ],
+        });
+        let mut refs = HashSet::new();
+        collect_media_refs(&node, &mut refs);
+        let mut refs: Vec<_> = refs.into_iter().collect();
+        refs.sort();
+        assert_eq!(refs, vec!["m1", "m2", "m3"]);
+    }
+
+    #[test]
+    fn test_per_file_fields() {
+        let one = vec!["Logo".to_string()];
+        assert_eq!(
+            per_file_fields("titles", "--title", &one, 2).unwrap(),
+            vec![("titles[0]".into(), "Logo".into()), ("titles[1]".into(), "Logo".into())]
+        );
+        let two = vec!["A".to_string(), "B".to_string()];
+        assert_eq!(per_file_fields("alts", "--alt", &two, 2).unwrap()[1].1, "B");
+        assert!(per_file_fields("alts", "--alt", &two, 3).is_err());
+        assert!(per_file_fields("alts", "--alt", &[], 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_inline_media_urls() {
+        let mut node = json!({"blocks": [
+            {"key": "hero", "type": "relationship_media", "value": "media:m1"},
+            {"key": "gallery", "type": "relationship_media", "value": [{"id": "m2"}, "m3"]},
+            {"key": "title", "type": "localized_text", "value": {"en": "Hi"}},
+        ]});
+        let urls = HashMap::from([
+            ("m1".to_string(), json!("https://cdn/m1.png")),
+            ("m2".to_string(), json!("https://cdn/m2.png")),
+            ("m3".to_string(), Value::Null),
+        ]);
+        inline_media_urls(&mut node, &urls);
+        assert_eq!(node["blocks"][0]["mediaUrls"], json!({"m1": "https://cdn/m1.png"}));
+        assert_eq!(
+            node["blocks"][1]["mediaUrls"],
+            json!({"m2": "https://cdn/m2.png", "m3": null})
+        );
+        assert!(node["blocks"][2].get("mediaUrls").is_none());
+    }
+
+    #[test]
+    fn test_attach_media_block_updates_existing() {
+        let mut node = json!({"blocks": [
+            {"type": "relationship_media", "id": "b1", "key": "hero", "properties": {}, "value": "media:old"}
+        ]});
+        attach_media_block(&mut node, "hero", "new").unwrap();
+        assert_eq!(node["blocks"].as_array().unwrap().len(), 1);
+        assert_eq!(node["blocks"][0]["value"], "media:new");
+        assert_eq!(node["blocks"][0]["id"], "b1");
+    }
+
+    #[test]
+    fn test_attach_media_block_appends_missing() {
+        let mut node = json!({"blocks": [
+            {"type": "text", "id": "b1", "key": "title", "properties": {}, "value": "Hi"}
+        ]});
+        attach_media_block(&mut node, "hero", "m1").unwrap();
+        assert_eq!(node["blocks"][1]["key"], "hero");
+        assert_eq!(node["blocks"][1]["value"], "media:m1");
+        assert!(attach_media_block(&mut node, "title", "m1").is_err());
+    }
+}
@@ -14,43 +14,1767 @@ pub mod platform;
 pub mod product;
 pub mod promo_code;
 pub mod provider;
+pub mod raw;
 pub mod service;
 pub mod shipping;
 pub mod workflow;
 
+use crate::client::ArkyClient;
 use crate::error::{CliError, Result};
+use crate::output::Format;
+use clap::Args;
 use serde_json::Value;
-use std::io::Read;
+use std::future::Future;
+use std::io::{IsTerminal, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// --expand-env: substitute `${VAR}` references in --data before parsing
+static EXPAND_ENV: AtomicBool = AtomicBool::new(false);
+
+pub fn set_expand_env(enabled: bool) {
+    EXPAND_ENV.store(enabled, Ordering::Relaxed);
+}
+
+/// --remember-id: save each created ID for later `@last` references
+static REMEMBER_ID: AtomicBool = AtomicBool::new(false);
+
+/// Argument that stands for the last remembered created ID
+const LAST_ID_TOKEN: &str = "@last";
+/// Cache entry (~/.arky/cache/last.json) holding `{"id", "type"}`
+const LAST_ID_CACHE_KEY: &str = "last";
+/// Flags whose `@name` value means a file, not `@last`
+const FILE_FLAGS: &[&str] = &["--data", "--input", "--from-cart"];
+
+pub fn set_remember_id(enabled: bool) {
+    REMEMBER_ID.store(enabled, Ordering::Relaxed);
+}
+
+/// --strict: reject --data keys outside an entity's known fields
+static STRICT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_strict(enabled: bool) {
+    STRICT.store(enabled, Ordering::Relaxed);
+}
 
 /// Parse --data flag: inline JSON string, "-" for stdin, or @filename
 pub fn parse_data(data: Option<&str>) -> Result<Value> {
-    match data {
-        None => Ok(Value::Object(serde_json::Map::new())),
+    let Some((text, source)) = read_data(data)? else {
+        return Ok(Value::Object(serde_json::Map::new()));
+    };
+    serde_json::from_str(&text)
+        .map_err(|e| CliError::InvalidInput(format!("Invalid JSON{source}: {e}")))
+}
+
+/// Parse --data as one or more records. Several JSON objects separated by
+/// newlines (NDJSON) yield one record each; a single object yields one.
+pub fn parse_records(data: Option<&str>) -> Result<Vec<Value>> {
+    let Some((text, source)) = read_data(data)? else {
+        return Ok(vec![Value::Object(serde_json::Map::new())]);
+    };
+    let records = serde_json::Deserializer::from_str(&text)
+        .into_iter::<Value>()
+        .enumerate()
+        .map(|(i, r)| {
+            r.map_err(|e| {
+                CliError::InvalidInput(format!("Invalid JSON{source} (record {}): {e}", i + 1))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if records.is_empty() {
+        return Err(CliError::InvalidInput(format!("No JSON records{source}")));
+    }
+    Ok(records)
+}
+
+/// Read the raw --data text along with a description of where it came from,
+/// expanding `${VAR}` references when --expand-env is set
+fn read_data(data: Option<&str>) -> Result<Option<(String, String)>> {
+    let (text, source) = match data {
+        None => return Ok(None),
         Some("-") => {
             let mut buf = String::new();
             std::io::stdin()
                 .read_to_string(&mut buf)
                 .map_err(|e| CliError::InvalidInput(format!("Failed to read stdin: {e}")))?;
-            serde_json::from_str(&buf)
-                .map_err(|e| CliError::InvalidInput(format!("Invalid JSON from stdin: {e}")))
+            (buf, " from stdin".to_string())
         }
         Some(s) if s.starts_with('@') => {
             let path = &s[1..];
             let content = std::fs::read_to_string(path)
                 .map_err(|e| CliError::InvalidInput(format!("Failed to read file {path}: {e}")))?;
-            serde_json::from_str(&content)
-                .map_err(|e| CliError::InvalidInput(format!("Invalid JSON in {path}: {e}")))
+            (content, format!(" in {path}"))
+        }
+        Some(s) => (s.to_string(), String::new()),
+    };
+    if !EXPAND_ENV.load(Ordering::Relaxed) {
+        return Ok(Some((text, source)));
+    }
+    let expanded = expand_env(&text, |name| std::env::var(name).ok())
+        .map_err(|e| CliError::InvalidInput(format!("{e}{source}")))?;
+    Ok(Some((expanded, source)))
+}
+
+/// Replace `${VAR}` and `${VAR:-default}` (default used when VAR is unset or
+/// empty) with values from `lookup`. `$${` is a literal `${`; any other `$` is
+/// left alone. Errors name the problem; the caller appends where --data came from.
+fn expand_env(
+    text: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> std::result::Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find('$') {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if let Some(after) = tail.strip_prefix("$${") {
+            out.push_str("${");
+            rest = after;
+            continue;
+        }
+        let Some(body) = tail.strip_prefix("${") else {
+            out.push('$');
+            rest = &tail[1..];
+            continue;
+        };
+        let end = body.find('}').ok_or("Unclosed ${ in --data")?;
+        let expr = &body[..end];
+        let (name, default) = match expr.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expr, None),
+        };
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("Invalid variable reference ${{{expr}}} in --data"));
+        }
+        let value = match default {
+            Some(default) => lookup(name)
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| default.to_string()),
+            None => lookup(name).ok_or_else(|| {
+                format!(
+                    "Environment variable {name} is not set \
+                     (use ${{{name}:-default}} for a fallback)"
+                )
+            })?,
+        };
+        out.push_str(&value);
+        rest = &body[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Build one create body per record: `{"key": KEY}` (when given) overlaid
+/// with the record. With several records each must carry its own key.
+pub fn keyed_records(key: Option<String>, records: Vec<Value>) -> Result<Vec<Value>> {
+    if key.is_some() && records.len() > 1 {
+        return Err(CliError::InvalidInput(
+            "KEY can't be used with multiple --data records; put a key in each record".into(),
+        ));
+    }
+    records
+        .into_iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let mut body = match key {
+                Some(ref k) => serde_json::json!({ "key": k }),
+                None => serde_json::json!({}),
+            };
+            merge_data(&mut body, record)?;
+            if body["key"].as_str().is_none_or(str::is_empty) {
+                return Err(CliError::InvalidInput(format!(
+                    "Record {} has no key. Pass KEY or include \"key\" in the data",
+                    i + 1
+                )));
+            }
+            Ok(body)
+        })
+        .collect()
+}
+
+/// Tell the user how far a multi-record create got before failing
+pub fn note_failed_record(index: usize, total: usize) {
+    if total > 1 {
+        crate::output::print_warning(&format!(
+            "Stopped at record {} of {total}; earlier records were already created",
+            index + 1
+        ));
+    }
+}
+
+/// ID of a newly created entity: top-level `id`, else `data.id`
+pub fn created_id(result: &Value) -> Option<&str> {
+    [&result["id"], &result["data"]["id"]]
+        .into_iter()
+        .find_map(|id| id.as_str().filter(|id| !id.is_empty()))
+}
+
+/// Print a create response, or just its ID with `--id-only`. With
+/// --remember-id the ID is also saved as `@last` (see `resolve_last_id`).
+pub fn print_created(result: &Value, kind: &str, id_only: bool, format: &Format) -> Result<()> {
+    if REMEMBER_ID.load(Ordering::Relaxed) {
+        if let Some(id) = created_id(result) {
+            // The entity exists either way; a cache failure must not hide its response
+            let last = serde_json::json!({ "id": id, "type": kind });
+            if let Err(e) = crate::cache::store(LAST_ID_CACHE_KEY, &last) {
+                crate::output::print_warning(&format!("Could not remember {id} as @last: {e}"));
+            }
+        }
+    }
+    if !id_only {
+        crate::output::print_output(result, format)?;
+        return Ok(());
+    }
+    let id = created_id(result).ok_or_else(|| {
+        CliError::InvalidInput("Create response has no id (checked id and data.id)".into())
+    })?;
+    println!("{id}");
+    Ok(())
+}
+
+/// Replace `@last` arguments with the ID saved by the last `--remember-id`
+/// create. Values of --data/--input are left alone, since `@last` there means
+/// a file named "last".
+pub fn resolve_last_id(args: Vec<String>) -> Result<Vec<String>> {
+    let takes_file = |flag: &str| FILE_FLAGS.contains(&flag);
+    let uses_last = |i: usize, arg: &str| {
+        arg == LAST_ID_TOKEN && (i == 0 || !takes_file(&args[i - 1]))
+            || arg
+                .split_once('=')
+                .is_some_and(|(flag, v)| v == LAST_ID_TOKEN && !takes_file(flag))
+    };
+    if !args.iter().enumerate().any(|(i, a)| uses_last(i, a)) {
+        return Ok(args);
+    }
+    let last = crate::cache::load(LAST_ID_CACHE_KEY, None)
+        .and_then(|saved| saved["id"].as_str().map(str::to_string))
+        .ok_or_else(|| {
+            CliError::InvalidInput(
+                "No ID saved for @last. Create something with --remember-id first".into(),
+            )
+        })?;
+    Ok(args
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| match arg.split_once('=') {
+            _ if !uses_last(i, arg) => arg.clone(),
+            Some((flag, _)) => format!("{flag}={last}"),
+            None => last.clone(),
+        })
+        .collect())
+}
+
+/// Fields commands fill in themselves before merging --data
+const AUTO_SET_FIELDS: &[&str] = &["key", "id", "businessId"];
+/// Auto-set fields that come from positional arguments (KEY, ID); these keep
+/// the argument's value over a different one in --data
+const POSITIONAL_FIELDS: &[&str] = &["key", "id"];
+
+/// Merge base object with data overlay. A non-object overlay is an error
+/// rather than being dropped, so `--data '[1, 2]'` can't silently send nothing.
+/// The overlay wins, except that `key`/`id` already set from a positional
+/// argument are kept; either way a differing auto-set field gets a warning.
+pub fn merge_data(base: &mut Value, overlay: Value) -> Result<()> {
+    let Value::Object(overlay_map) = overlay else {
+        return Err(CliError::InvalidInput(format!(
+            "--data must be a JSON object, got {}",
+            json_type(&overlay)
+        )));
+    };
+    let Value::Object(base_map) = base else {
+        return Ok(());
+    };
+    for (field, value) in overlay_map {
+        let collides = AUTO_SET_FIELDS.contains(&field.as_str())
+            && base_map.get(&field).is_some_and(|current| *current != value);
+        if collides && POSITIONAL_FIELDS.contains(&field.as_str()) {
+            crate::output::print_warning(&format!(
+                "Ignoring \"{field}\": {value} in --data; using {} from the command line",
+                base_map[&field]
+            ));
+            continue;
+        }
+        if collides {
+            crate::output::print_warning(&format!(
+                "--data \"{field}\": {value} replaces {}",
+                base_map[&field]
+            ));
+        }
+        base_map.insert(field, value);
+    }
+    Ok(())
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Subcommands whose create/update take `--example` to print a skeleton
+/// instead of calling the API
+pub trait Example {
+    fn is_example(&self) -> bool;
+}
+
+macro_rules! impl_example {
+    ($($command:ty),*) => {
+        $(impl Example for $command {
+            fn is_example(&self) -> bool {
+                matches!(
+                    self,
+                    Self::Create { example: true, .. } | Self::Update { example: true, .. }
+                )
+            }
+        })*
+    };
+}
+
+impl_example!(
+    agent::AgentCommand,
+    audience::AudienceCommand,
+    booking::BookingCommand,
+    business::BusinessCommand,
+    node::NodeCommand,
+    order::OrderCommand,
+    product::ProductCommand,
+    promo_code::PromoCodeCommand,
+    provider::ProviderCommand,
+    service::ServiceCommand,
+    workflow::WorkflowCommand
+);
+
+/// Print a `--example` skeleton as pretty JSON, whatever --format says, so it
+/// can be saved and passed back with `--data @file`
+pub fn print_example(example: &Value) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(example)?);
+    Ok(())
+}
+
+/// Write bytes to a file, or to stdout when `out` is "-"
+pub fn write_out(out: &str, bytes: &[u8]) -> Result<()> {
+    if out == "-" {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(bytes)?;
+        stdout.flush()?;
+    } else {
+        std::fs::write(out, bytes)
+            .map_err(|e| CliError::InvalidInput(format!("Failed to write {out}: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Upper bound on items collected by `fetch_all`, so a runaway cursor can't loop forever
+pub const FETCH_ALL_CAP: usize = 10_000;
+
+/// `--limit` when none is given
+pub const DEFAULT_LIST_LIMIT: u32 = 20;
+
+/// Paging flags shared by every list command.
+///
+/// `--limit` is the size of the one page returned. With `--all` (or `--limit 0`)
+/// every page is fetched instead: `--page-size` is then the per-request size and
+/// `--limit` caps the total.
+#[derive(Args, Debug, Clone)]
+pub struct ListArgs {
+    /// Items to return (default 20); with --all, the total cap. 0 = every page
+    #[arg(long)]
+    pub limit: Option<u32>,
+    /// Cursor from a previous page
+    #[arg(long)]
+    pub cursor: Option<String>,
+    /// Fetch every page (up to 10000 items, or --limit if given)
+    #[arg(long)]
+    pub all: bool,
+    /// Items per request while fetching every page
+    #[arg(long, default_value = "100")]
+    pub page_size: u32,
+    /// Print only the number of matching items
+    #[arg(long)]
+    pub count: bool,
+    /// Requests in flight at once while fetching every page. Pages of one list
+    /// follow cursors in order, so there any value above 1 prefetches the next
+    /// page while the current one is printed
+    #[arg(long, default_value_t = DEFAULT_CONCURRENCY)]
+    pub concurrency: usize,
+}
+
+impl ListArgs {
+    /// Override the one-page default for endpoints that return more by default
+    pub fn with_default_limit(mut self, limit: u32) -> Self {
+        if !self.all {
+            self.limit.get_or_insert(limit);
+        }
+        self
+    }
+
+    /// Total cap when fetching every page, or None for a single page
+    fn unbounded_cap(&self) -> Option<usize> {
+        match self.limit {
+            Some(0) => Some(FETCH_ALL_CAP),
+            Some(n) if self.all => Some(n as usize),
+            _ if self.all => Some(FETCH_ALL_CAP),
+            _ => None,
+        }
+    }
+}
+
+/// Follow `cursor` through a list endpoint and collect every item in `data`
+pub async fn fetch_all(
+    client: &ArkyClient,
+    path: &str,
+    params: &[(&str, &str)],
+) -> Result<Vec<Value>> {
+    fetch_pages(client, path, params, 100, FETCH_ALL_CAP, DEFAULT_CONCURRENCY).await
+}
+
+/// Requests in flight at once unless --concurrency says otherwise
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// `fetch_all` over several independent list paths, up to `concurrency` at a
+/// time; results come back in `paths` order. Pages within one list stay
+/// sequential: cursors are opaque, so the next request needs the previous page.
+pub async fn fetch_all_concurrent(
+    client: &ArkyClient,
+    paths: &[String],
+    concurrency: usize,
+) -> Result<Vec<Vec<Value>>> {
+    let mut results = vec![Vec::new(); paths.len()];
+    let mut pending = paths.iter().cloned().enumerate();
+    let mut tasks = tokio::task::JoinSet::new();
+    loop {
+        while tasks.len() < concurrency.max(1) {
+            let Some((i, path)) = pending.next() else {
+                break;
+            };
+            let client = client.clone();
+            tasks.spawn(async move { (i, fetch_all(&client, &path, &[]).await) });
+        }
+        let Some(joined) = tasks.join_next().await else {
+            break;
+        };
+        let (i, items) =
+            joined.map_err(|e| CliError::InvalidInput(format!("List fetch task failed: {e}")))?;
+        results[i] = items?;
+    }
+    Ok(results)
+}
+
+/// GET several paths, up to `DEFAULT_CONCURRENCY` at a time. Each path gets its
+/// own result, in `paths` order, so one failure doesn't hide the rest.
+pub async fn get_concurrent(client: &ArkyClient, paths: &[String]) -> Result<Vec<Result<Value>>> {
+    let mut results: Vec<Option<Result<Value>>> = paths.iter().map(|_| None).collect();
+    let mut pending = paths.iter().cloned().enumerate();
+    let mut tasks = tokio::task::JoinSet::new();
+    loop {
+        while tasks.len() < DEFAULT_CONCURRENCY {
+            let Some((i, path)) = pending.next() else {
+                break;
+            };
+            let client = client.clone();
+            tasks.spawn(async move { (i, client.get(&path, &[]).await) });
+        }
+        let Some(joined) = tasks.join_next().await else {
+            break;
+        };
+        let (i, result) =
+            joined.map_err(|e| CliError::InvalidInput(format!("Fetch task failed: {e}")))?;
+        results[i] = Some(result);
+    }
+    Ok(results.into_iter().flatten().collect())
+}
+
+/// `fetch_all` with an explicit per-request size and a total cap. Only the
+/// safety cap (`FETCH_ALL_CAP`) warns; a smaller cap is what the caller asked for.
+async fn fetch_pages(
+    client: &ArkyClient,
+    path: &str,
+    params: &[(&str, &str)],
+    page_size: u32,
+    cap: usize,
+    concurrency: usize,
+) -> Result<Vec<Value>> {
+    let mut items = Vec::new();
+    let collect = |page: Vec<Value>| items.extend(page);
+    for_each_page(client, path, params, page_size, cap, concurrency, collect).await?;
+    Ok(items)
+}
+
+/// Walk every page of a list, handing each page's items (trimmed to `cap`) to
+/// `on_page`; returns the number of items seen. With `concurrency` above 1 the
+/// next page is requested before `on_page` runs, so printing overlaps the fetch.
+async fn for_each_page(
+    client: &ArkyClient,
+    path: &str,
+    params: &[(&str, &str)],
+    page_size: u32,
+    cap: usize,
+    concurrency: usize,
+    mut on_page: impl FnMut(Vec<Value>),
+) -> Result<usize> {
+    let cap = cap.min(FETCH_ALL_CAP);
+    let page_size = page_size.max(1).to_string();
+    let request = |cursor: Option<String>| {
+        let client = client.clone();
+        let path = path.to_string();
+        let mut query: Vec<(String, String)> =
+            params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        query.push(("limit".into(), page_size.clone()));
+        query.extend(cursor.map(|c| ("cursor".to_string(), c)));
+        tokio::spawn(async move {
+            let query: Vec<(&str, &str)> =
+                query.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+            client.get(&path, &query).await
+        })
+    };
+    let mut seen = 0;
+    let mut cursor: Option<String> = None;
+    let mut next = Some(request(None));
+    while let Some(pending) = next.take() {
+        let page = pending
+            .await
+            .map_err(|e| CliError::InvalidInput(format!("Page fetch task failed: {e}")))??;
+        let mut data = page_items(&page).cloned().unwrap_or_default();
+        if data.is_empty() {
+            break;
+        }
+        let returned = data.len();
+        data.truncate(cap - seen);
+        seen += data.len();
+        let more = next_cursor(&page).filter(|c| cursor.as_deref() != Some(c.as_str()));
+        let capped = seen >= cap;
+        if concurrency > 1 && !capped {
+            next = more.clone().map(|c| request(Some(c)));
+        }
+        // Items were left behind only if this page was trimmed or has a successor
+        let left_behind = data.len() < returned || more.is_some();
+        on_page(data);
+        if capped {
+            if cap == FETCH_ALL_CAP && left_behind {
+                crate::output::print_warning(&format!(
+                    "Stopped after {FETCH_ALL_CAP} items; narrow the filters to see the rest"
+                ));
+            }
+            break;
+        }
+        if next.is_none() {
+            next = more.clone().map(|c| request(Some(c)));
+        }
+        cursor = more;
+    }
+    Ok(seen)
+}
+
+/// Cursor fields seen across list endpoints, in lookup order
+const CURSOR_PATHS: &[&[&str]] = &[
+    &["cursor"],
+    &["nextCursor"],
+    &["meta", "cursor"],
+    &["meta", "nextCursor"],
+    &["pagination", "cursor"],
+    &["pagination", "nextCursor"],
+];
+
+/// The cursor for the next page, if the response has a non-empty one
+pub fn next_cursor(page: &Value) -> Option<String> {
+    CURSOR_PATHS.iter().find_map(|path| {
+        path.iter()
+            .try_fold(page, |v, key| v.get(key))
+            .and_then(Value::as_str)
+            .filter(|c| !c.is_empty())
+            .map(str::to_string)
+    })
+}
+
+/// The items of a list response: `data`, `items`, or a top-level array
+pub fn page_items(page: &Value) -> Option<&Vec<Value>> {
+    page.as_array()
+        .or_else(|| page.get("data").and_then(Value::as_array))
+        .or_else(|| page.get("items").and_then(Value::as_array))
+}
+
+/// Look up the ID of the entity with key `key` by listing `path` with a key
+/// filter. Only exact key matches count; zero or several is an error.
+pub async fn resolve_key(client: &ArkyClient, path: &str, kind: &str, key: &str) -> Result<String> {
+    let page = client.get(path, &[("key", key), ("limit", "100")]).await?;
+    let ids = ids_with_key(&page, key);
+    match ids.as_slice() {
+        [id] => Ok(id.to_string()),
+        [] => Err(CliError::InvalidInput(format!("No {kind} with key '{key}'"))),
+        _ => Err(CliError::InvalidInput(format!(
+            "{} {kind}s have key '{key}' ({}). Pass the ID instead",
+            ids.len(),
+            ids.join(", ")
+        ))),
+    }
+}
+
+/// POST a create body to the collection at `path`. A taken key is reported
+/// as such, or with --upsert the entity holding the key is updated instead
+/// (full PUT of the same body).
+pub async fn create_or_update(
+    client: &ArkyClient,
+    path: &str,
+    kind: &str,
+    body: &Value,
+    upsert: bool,
+) -> Result<Value> {
+    match client.post(path, body).await {
+        Err(e) if is_key_conflict(&e) => {}
+        other => return other,
+    }
+    let key = body["key"].as_str().unwrap_or_default();
+    if !upsert {
+        return Err(CliError::InvalidInput(format!(
+            "A {kind} with key '{key}' already exists. Use --upsert to update it instead"
+        )));
+    }
+    let id = resolve_key(client, path, kind, key).await?;
+    crate::output::print_warning(&format!("Key '{key}' exists; updating {kind} {id}"));
+    let mut body = body.clone();
+    body["id"] = Value::String(id.clone());
+    client.put(&format!("{path}/{id}"), &body).await
+}
+
+/// A create rejected because the key is in use: a 409, or a validation error
+/// on `key` saying so
+fn is_key_conflict(err: &CliError) -> bool {
+    let CliError::Api {
+        status,
+        validation_errors,
+        ..
+    } = err
+    else {
+        return false;
+    };
+    let taken = |msg: &str| {
+        let msg = msg.to_lowercase();
+        ["exist", "duplicate", "taken", "unique"].iter().any(|w| msg.contains(w))
+    };
+    *status == 409 || validation_errors.iter().any(|ve| ve.field == "key" && taken(&ve.error))
+}
+
+fn ids_with_key<'a>(page: &'a Value, key: &str) -> Vec<&'a str> {
+    page_items(page)
+        .into_iter()
+        .flatten()
+        .filter(|item| item["key"] == key)
+        .filter_map(|item| item["id"].as_str())
+        .collect()
+}
+
+/// GET a list endpoint with the shared paging flags and print the result.
+/// With --ndjson, every-page items are printed as each page arrives instead
+/// of being collected first.
+pub async fn print_list(
+    client: &ArkyClient,
+    path: &str,
+    params: &[(&str, &str)],
+    list: &ListArgs,
+    format: &Format,
+) -> Result<()> {
+    match list.unbounded_cap() {
+        Some(cap) if !list.count && crate::output::streams_lists() => {
+            let stream = |page: Vec<Value>| crate::output::stream_items(&page);
+            let (page_size, concurrency) = (list.page_size, list.concurrency);
+            for_each_page(client, path, params, page_size, cap, concurrency, stream).await?;
+            Ok(())
+        }
+        _ => crate::output::print_output(&fetch_list(client, path, params, list).await?, format),
+    }
+}
+
+/// GET a list endpoint with the shared paging flags. `params` holds the
+/// command's filters; limit/cursor come from `list`. Fetching every page
+/// returns `{"data": [...]}`; `--count` returns just the number.
+pub async fn fetch_list(
+    client: &ArkyClient,
+    path: &str,
+    params: &[(&str, &str)],
+    list: &ListArgs,
+) -> Result<Value> {
+    if list.count {
+        return Ok(serde_json::json!(count_list(client, path, params, list.page_size).await?));
+    }
+    if let Some(cap) = list.unbounded_cap() {
+        let items =
+            fetch_pages(client, path, params, list.page_size, cap, list.concurrency).await?;
+        return Ok(serde_json::json!({ "data": items }));
+    }
+    let limit = list.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    let limit_param = limit.to_string();
+    let mut page_params: Vec<(&str, &str)> = params.to_vec();
+    page_params.push(("limit", &limit_param));
+    if let Some(ref c) = list.cursor {
+        page_params.push(("cursor", c));
+    }
+    let page = client.get(path, &page_params).await?;
+    if let Some(returned) = capped_page(&page, limit) {
+        crate::output::print_warning(&format!(
+            "Server returned {returned} of the {limit} requested items and has more pages. \
+             Use --all to fetch every page, or --cursor to continue"
+        ));
+    }
+    Ok(page)
+}
+
+/// The item count of a page that came back short of `limit` while still
+/// offering a next cursor, i.e. the server capped the page size
+fn capped_page(page: &Value, limit: u32) -> Option<usize> {
+    let returned = page_items(page)?.len();
+    (returned < limit as usize && next_cursor(page).is_some()).then_some(returned)
+}
+
+/// Use the server's total when it reports one, else count every page client-side
+pub async fn count_list(
+    client: &ArkyClient,
+    path: &str,
+    params: &[(&str, &str)],
+    page_size: u32,
+) -> Result<usize> {
+    let mut probe = params.to_vec();
+    probe.push(("limit", "1"));
+    if let Some(total) = server_total(&client.get(path, &probe).await?) {
+        return Ok(total);
+    }
+    let items = fetch_pages(client, path, params, page_size, FETCH_ALL_CAP, DEFAULT_CONCURRENCY);
+    Ok(items.await?.len())
+}
+
+fn server_total(page: &Value) -> Option<usize> {
+    ["total", "count"]
+        .iter()
+        .find_map(|field| page[*field].as_u64())
+        .map(|n| n as usize)
+}
+
+/// Keep only list entries matching `pattern`: a case-insensitive regex, or a
+/// plain substring when it isn't valid regex. Works on a top-level array or an
+/// `items`/`data` array; strings match directly, objects by any string field.
+pub fn grep_items(value: &mut Value, pattern: &str) {
+    let re = regex::RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .unwrap_or_else(|_| {
+            regex::RegexBuilder::new(&regex::escape(pattern))
+                .case_insensitive(true)
+                .build()
+                .expect("escaped pattern is valid")
+        });
+    let matches = |item: &Value| match item {
+        Value::String(s) => re.is_match(s),
+        Value::Object(obj) => obj.values().any(|v| v.as_str().is_some_and(|s| re.is_match(s))),
+        _ => false,
+    };
+    let items = if value.is_array() {
+        value.as_array_mut()
+    } else if value.get("items").is_some_and(Value::is_array) {
+        value.get_mut("items").and_then(Value::as_array_mut)
+    } else {
+        value.get_mut("data").and_then(Value::as_array_mut)
+    };
+    if let Some(items) = items {
+        items.retain(matches);
+    }
+}
+
+/// Parse a time filter into epoch seconds. Accepts epoch seconds, a
+/// YYYY-MM-DD date (UTC midnight), `now`, or an offset from now: `7d`, `12h`,
+/// `30m`, `45s`, `2w` (or `-7d`) are that long ago, a leading `+` (`+3d`) is in
+/// the future.
+pub fn parse_time_arg(input: &str) -> Result<i64> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    parse_time_at(input, now)
+}
+
+fn parse_time_at(input: &str, now: i64) -> Result<i64> {
+    let input = input.trim();
+    if let Ok(secs) = input.parse::<i64>() {
+        return Ok(secs);
+    }
+    let invalid = || {
+        CliError::InvalidInput(format!(
+            "Invalid time '{input}'. Use epoch seconds, YYYY-MM-DD, now, or an offset like 7d, 12h, +3d"
+        ))
+    };
+    if input.eq_ignore_ascii_case("now") {
+        return Ok(now);
+    }
+    let (sign, offset) = match input.strip_prefix('+') {
+        Some(rest) => (1, rest),
+        None => (-1, input.strip_prefix('-').unwrap_or(input)),
+    };
+    if let Some(unit) = offset.chars().last().filter(char::is_ascii_alphabetic) {
+        let amount: u32 = offset[..offset.len() - 1].parse().map_err(|_| invalid())?;
+        let amount = i64::from(amount);
+        let secs = match unit.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 3_600,
+            'd' => 86_400,
+            'w' => 7 * 86_400,
+            _ => return Err(invalid()),
+        };
+        return Ok(now + sign * amount * secs);
+    }
+    let parts: Vec<&str> = input.split('-').collect();
+    let [y, m, d] = parts.as_slice() else {
+        return Err(invalid());
+    };
+    let (y, m, d): (i64, i64, i64) = (
+        y.parse().map_err(|_| invalid())?,
+        m.parse().map_err(|_| invalid())?,
+        d.parse().map_err(|_| invalid())?,
+    );
+    let leap = y % 4 == 0 && (y % 100 != 0 || y % 400 == 0);
+    let days_in_month = match m {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if !(1..=12).contains(&m) || !(1..=days_in_month).contains(&d) {
+        return Err(invalid());
+    }
+    // Days since 1970-01-01 (Howard Hinnant's days_from_civil)
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Ok((era * 146_097 + doe - 719_468) * 86_400)
+}
+
+/// GET an entity, change only its status, and PUT the full object back so
+/// required fields (key, slug, blocks, ...) are preserved
+pub async fn set_status(client: &ArkyClient, path: &str, status: &str) -> Result<Value> {
+    let mut entity = client.get(path, &[]).await?;
+    entity["status"] = Value::String(status.to_string());
+    client.put(path, &entity).await
+}
+
+/// Set the amount of the `{market, currency}` entry in a prices array, adding
+/// the entry when there is none. Codes match case-insensitively.
+pub fn set_price(prices: &mut Value, market: &str, currency: &str, amount: u64) {
+    if !prices.is_array() {
+        *prices = Value::Array(Vec::new());
+    }
+    let Some(entries) = prices.as_array_mut() else {
+        return;
+    };
+    let matches = |price: &Value, field: &str, code: &str| {
+        price[field].as_str().is_some_and(|v| v.eq_ignore_ascii_case(code))
+    };
+    match entries
+        .iter_mut()
+        .find(|p| matches(p, "market", market) && matches(p, "currency", currency))
+    {
+        Some(price) => price["amount"] = amount.into(),
+        None => entries.push(serde_json::json!({
+            "market": market,
+            "currency": currency,
+            "amount": amount
+        })),
+    }
+}
+
+/// The entry of an array field whose `field` equals `value`, or an error
+/// listing the values that exist (e.g. a product's variants by key)
+pub fn find_entry<'a>(
+    entity: &'a mut Value,
+    array: &str,
+    field: &str,
+    value: &str,
+) -> Result<&'a mut Value> {
+    let entries = entity[array].as_array_mut().map(Vec::as_mut_slice).unwrap_or_default();
+    let known: Vec<String> = entries
+        .iter()
+        .filter_map(|e| e[field].as_str().map(String::from))
+        .collect();
+    entries
+        .iter_mut()
+        .find(|e| e[field].as_str() == Some(value))
+        .ok_or_else(|| {
+            CliError::InvalidInput(format!(
+                "No {array} entry with {field} '{value}' (found: {})",
+                if known.is_empty() { "none".to_string() } else { known.join(", ") }
+            ))
+        })
+}
+
+/// Fields each entity requires on a full update (PUT). Missing ones are copied
+/// from the current entity so a partial payload can't wipe them.
+pub const NODE_REQUIRED_FIELDS: &[&str] =
+    &["key", "slug", "status", "writeAccess", "audienceIds", "blocks"];
+pub const PRODUCT_REQUIRED_FIELDS: &[&str] = &[
+    "key", "slug", "status", "audienceIds", "networkIds", "filters", "blocks", "variants",
+];
+pub const SERVICE_REQUIRED_FIELDS: &[&str] = &[
+    "key", "slug", "status", "audienceIds", "networkIds", "filters", "blocks", "providers",
+];
+pub const PROVIDER_REQUIRED_FIELDS: &[&str] = &[
+    "key", "slug", "status", "audienceIds", "networkIds", "filters", "blocks", "concurrentLimit",
+];
+
+/// Fill required fields missing from an update body with the entity's current
+/// values (one GET, only when something is missing). Warns about what was kept.
+pub async fn preserve_required_fields(
+    client: &ArkyClient,
+    path: &str,
+    body: &mut Value,
+    fields: &[&str],
+) -> Result<()> {
+    if fields.iter().all(|f| body.get(f).is_some()) {
+        return Ok(());
+    }
+    let current = client.get(path, &[]).await?;
+    let kept = fill_missing_fields(body, &current, fields);
+    if !kept.is_empty() {
+        crate::output::print_warning(&format!(
+            "Kept current {} (not in --data). Use --force to send the payload as-is",
+            kept.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Copy `fields` missing from `body` out of `current`; returns the ones copied
+pub fn fill_missing_fields<'a>(
+    body: &mut Value,
+    current: &Value,
+    fields: &[&'a str],
+) -> Vec<&'a str> {
+    let mut kept = Vec::new();
+    for field in fields {
+        if body.get(field).is_none() {
+            if let Some(value) = current.get(field) {
+                body[*field] = value.clone();
+                kept.push(*field);
+            }
+        }
+    }
+    kept
+}
+
+/// Fields the server sets on every entity; a GET round-trip may carry them back
+const SERVER_FIELDS: &[&str] = &["id", "businessId", "createdAt", "updatedAt"];
+
+/// With --strict, fail on top-level keys of `body` that are neither in `known`
+/// nor server-set, so a typo like `blockz` isn't silently dropped by the API
+pub fn check_known_fields(body: &Value, known: &[&str], entity: &str) -> Result<()> {
+    if !STRICT.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    let unknown = unknown_fields(body, known);
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    Err(CliError::InvalidInput(format!(
+        "Unknown {entity} field(s) in --data: {}. Known fields: {}",
+        unknown.join(", "),
+        known.join(", ")
+    )))
+}
+
+fn unknown_fields<'a>(body: &'a Value, known: &[&str]) -> Vec<&'a str> {
+    body.as_object()
+        .map(|obj| {
+            obj.keys()
+                .map(String::as_str)
+                .filter(|k| !known.contains(k) && !SERVER_FIELDS.contains(k))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Structural diff of two JSON values as a flat list of
+/// `{"op": "added"|"removed"|"changed", "path", "from", "to"}` entries.
+/// Arrays of blocks (objects with unique `key`s) are matched by key, so a
+/// reordered or edited block shows up as `blocks[title].value.en`; other
+/// arrays are compared as a whole.
+pub fn json_diff(old: &Value, new: &Value) -> Vec<Value> {
+    let mut changes = Vec::new();
+    diff_into("", old, new, &mut changes);
+    changes
+}
+
+fn diff_into(path: &str, old: &Value, new: &Value, out: &mut Vec<Value>) {
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            let child = |k: &str| {
+                if path.is_empty() {
+                    k.to_string()
+                } else {
+                    format!("{path}.{k}")
+                }
+            };
+            let a = a.iter().map(|(k, v)| (k.as_str(), v)).collect();
+            let b = b.iter().map(|(k, v)| (k.as_str(), v)).collect();
+            diff_entries(a, b, child, out);
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            if let (Some(a), Some(b)) = (block_entries(a), block_entries(b)) {
+                diff_entries(a, b, |k| format!("{path}[{k}]"), out);
+            } else if a != b {
+                out.push(changed(path, old, new));
+            }
+        }
+        _ if old != new => {
+            out.push(changed(path, old, new));
+        }
+        _ => {}
+    }
+}
+
+fn changed(path: &str, from: &Value, to: &Value) -> Value {
+    serde_json::json!({"op": "changed", "path": path, "from": from, "to": to})
+}
+
+fn diff_entries(
+    old: Vec<(&str, &Value)>,
+    new: Vec<(&str, &Value)>,
+    child: impl Fn(&str) -> String,
+    out: &mut Vec<Value>,
+) {
+    fn find<'a>(entries: &[(&str, &'a Value)], key: &str) -> Option<&'a Value> {
+        entries.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    }
+    for (key, from) in &old {
+        match find(&new, key) {
+            Some(to) => diff_into(&child(key), from, to, out),
+            None => {
+                out.push(serde_json::json!({"op": "removed", "path": child(key), "from": from}));
+            }
+        }
+    }
+    for (key, to) in &new {
+        if find(&old, key).is_none() {
+            out.push(serde_json::json!({"op": "added", "path": child(key), "to": to}));
+        }
+    }
+}
+
+/// Blocks keyed by their `key`, or None unless every item has a unique one
+fn block_entries(items: &[Value]) -> Option<Vec<(&str, &Value)>> {
+    let entries: Vec<(&str, &Value)> = items
+        .iter()
+        .map(|item| item.get("key").and_then(Value::as_str).map(|k| (k, item)))
+        .collect::<Option<_>>()?;
+    let mut keys: Vec<&str> = entries.iter().map(|(k, _)| *k).collect();
+    keys.sort_unstable();
+    keys.dedup();
+    (keys.len() == entries.len() && !entries.is_empty()).then_some(entries)
+}
+
+/// Call `fetch` every `interval` until `done` accepts the result, returning
+/// that value. Gives up after `timeout` with an error naming `what` and the
+/// last value's status; Ctrl-C stops early with `CliError::Interrupted`.
+pub async fn poll_until<F, Fut>(
+    what: &str,
+    mut fetch: F,
+    done: impl Fn(&Value) -> bool,
+    interval: Duration,
+    timeout: Duration,
+) -> Result<Value>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Value>>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let value = fetch().await?;
+        if done(&value) {
+            return Ok(value);
+        }
+        let status = value["status"]
+            .as_str()
+            .or_else(|| value["data"]["status"].as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        if tokio::time::Instant::now() + interval > deadline {
+            return Err(CliError::InvalidInput(format!(
+                "Timed out after {}s waiting for {what} (last status: {status})",
+                timeout.as_secs()
+            )));
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = tokio::signal::ctrl_c() => {
+                return Err(CliError::Interrupted {
+                    what: what.to_string(),
+                    last: value,
+                });
+            }
+        }
+    }
+}
+
+/// Ask a yes/no question on stderr. Without a terminal there is nobody to ask,
+/// so this fails and points at `--yes`.
+pub fn confirm(prompt: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        return Err(CliError::InvalidInput(
+            "Confirmation required but stdin is not a terminal. Pass --yes to proceed".into(),
+        ));
+    }
+    eprint!("{prompt} [y/N] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Shared flags for deleting a single entity by ID
+#[derive(Args, Debug, Clone)]
+pub struct DeleteArgs {
+    /// Fetch the entity and show its key/title, then ask before deleting
+    #[arg(long)]
+    pub show: bool,
+    /// Skip the --show confirmation
+    #[arg(long, short = 'y', requires = "show")]
+    pub yes: bool,
+    /// Treat an already-deleted ID (404) as success
+    #[arg(long)]
+    pub ignore_missing: bool,
+    /// Delete every ID read from stdin (one per line, or a JSON array)
+    #[arg(long, conflicts_with = "show")]
+    pub stdin: bool,
+    /// With --stdin, stop at the first failed delete
+    #[arg(long, requires = "stdin")]
+    pub stop_on_error: bool,
+}
+
+/// With --show, print what `path` points at and ask whether to delete it.
+/// Returns false when the user declines; a missing entity fails the GET
+/// unless --ignore-missing is set.
+pub async fn confirm_delete(
+    client: &ArkyClient,
+    path: &str,
+    kind: &str,
+    args: &DeleteArgs,
+    format: &Format,
+) -> Result<bool> {
+    if !args.show {
+        return Ok(true);
+    }
+    let entity = match client.get(path, &[]).await {
+        Err(CliError::Api { status: 404, .. }) if args.ignore_missing => {
+            note_missing(kind);
+            return Ok(false);
+        }
+        other => other?,
+    };
+    crate::output::print_preview(&delete_summary(&entity), format);
+    if !args.yes && !confirm(&format!("Delete this {kind}?"))? {
+        crate::output::print_warning("Delete cancelled");
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// DELETE `path` and return whether anything was deleted. With
+/// --ignore-missing a 404 counts as already deleted, so cleanup scripts can
+/// run twice.
+pub async fn delete_entity(
+    client: &ArkyClient,
+    path: &str,
+    kind: &str,
+    ignore_missing: bool,
+) -> Result<bool> {
+    deleted(client.delete(path).await, kind, ignore_missing)
+}
+
+fn deleted(result: Result<Value>, kind: &str, ignore_missing: bool) -> Result<bool> {
+    match result {
+        Ok(_) => Ok(true),
+        Err(CliError::Api { status: 404, .. }) if ignore_missing => {
+            note_missing(kind);
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// `delete --stdin`: DELETE `{collection}/{id}` for each ID piped in, noting
+/// each result. Failures don't stop the run (unless --stop-on-error) but make
+/// it fail once every ID has been tried.
+pub async fn delete_from_stdin(
+    client: &ArkyClient,
+    collection: &str,
+    kind: &str,
+    args: &DeleteArgs,
+) -> Result<()> {
+    if std::io::stdin().is_terminal() {
+        return Err(CliError::InvalidInput(format!(
+            "--stdin expects {kind} IDs piped in, e.g. arky {kind} list --all --pointer /data \
+             | arky {kind} delete --stdin"
+        )));
+    }
+    let input = std::io::read_to_string(std::io::stdin())
+        .map_err(|e| CliError::InvalidInput(format!("Failed to read stdin: {e}")))?;
+    let ids = parse_ids(&input)?;
+    let (mut deleted, mut failed) = (0, 0);
+    for (i, id) in ids.iter().enumerate() {
+        let path = format!("{collection}/{id}");
+        match delete_entity(client, &path, kind, args.ignore_missing).await {
+            Ok(true) => {
+                deleted += 1;
+                crate::output::print_success(&format!("Deleted {kind} {id}"));
+            }
+            Ok(false) => {}
+            Err(e) => {
+                failed += 1;
+                crate::output::print_error(&format!("{id}: {e}"));
+                if args.stop_on_error {
+                    return Err(CliError::InvalidInput(format!(
+                        "Stopped at {kind} {} of {}; earlier ones were already deleted",
+                        i + 1,
+                        ids.len()
+                    )));
+                }
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(CliError::InvalidInput(format!(
+            "{failed} of {} {kind} deletes failed",
+            ids.len()
+        )));
+    }
+    crate::output::print_success(&format!("Deleted {deleted} of {} {kind} IDs", ids.len()));
+    Ok(())
+}
+
+/// IDs from a JSON array (of IDs or of objects with an `id`) or one per line;
+/// blank lines are skipped
+fn parse_ids(input: &str) -> Result<Vec<String>> {
+    let ids: Vec<String> = if input.trim_start().starts_with('[') {
+        let items: Vec<Value> = serde_json::from_str(input)
+            .map_err(|e| CliError::InvalidInput(format!("Invalid JSON array of IDs: {e}")))?;
+        items
+            .iter()
+            .map(|item| {
+                item.as_str().or_else(|| item["id"].as_str()).map(String::from).ok_or_else(|| {
+                    CliError::InvalidInput(format!("Not an ID or an object with an id: {item}"))
+                })
+            })
+            .collect::<Result<_>>()?
+    } else {
+        input.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect()
+    };
+    if ids.is_empty() {
+        return Err(CliError::InvalidInput("No IDs on stdin".into()));
+    }
+    Ok(ids)
+}
+
+fn note_missing(kind: &str) {
+    crate::output::print_success(&format!("No such {kind}; nothing to delete"));
+}
+
+/// The identifying fields of an entity: id, key, status and a readable title
+/// (a `name`/`title` field, else the title/name block's text)
+fn delete_summary(entity: &Value) -> Value {
+    let mut summary = serde_json::Map::new();
+    for field in ["id", "key", "status"] {
+        if let Some(value) = entity.get(field) {
+            summary.insert(field.to_string(), value.clone());
         }
-        Some(s) => serde_json::from_str(s)
-            .map_err(|e| CliError::InvalidInput(format!("Invalid JSON: {e}"))),
     }
+    let blocks = entity["blocks"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let title = ["title", "name"].iter().find_map(|key| {
+        let block = blocks.iter().find(|b| b["key"] == *key).map(|b| &b["value"]);
+        display_text(&entity[*key]).or_else(|| block.and_then(display_text))
+    });
+    if let Some(title) = title {
+        summary.insert("title".to_string(), Value::String(title));
+    }
+    Value::Object(summary)
+}
+
+/// A plain or localized (`{"en": ...}`, English first) string value
+fn display_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Object(map) => map
+            .get("en")
+            .and_then(Value::as_str)
+            .or_else(|| map.values().find_map(Value::as_str))
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+/// Fields every address must carry (state is optional outside the US)
+pub const ADDRESS_REQUIRED_FIELDS: &[&str] = &["name", "street1", "city", "postalCode", "country"];
+
+/// Check an address locally before it reaches the server, naming each missing
+/// field and rejecting a `country` that isn't a 2-letter ISO code. `label` is
+/// how the address is referred to in errors (e.g. "toAddress").
+pub fn validate_address(address: &Value, label: &str) -> Result<()> {
+    if !address.is_object() {
+        return Err(CliError::InvalidInput(format!("{label} must be a JSON object")));
+    }
+    let missing: Vec<&str> = ADDRESS_REQUIRED_FIELDS
+        .iter()
+        .copied()
+        .filter(|f| address[*f].as_str().is_none_or(|v| v.trim().is_empty()))
+        .collect();
+    if !missing.is_empty() {
+        return Err(CliError::InvalidInput(format!(
+            "{label} is missing required field(s): {}",
+            missing.join(", ")
+        )));
+    }
+    let country = address["country"].as_str().unwrap_or_default().trim();
+    if country.len() != 2 || !country.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(CliError::InvalidInput(format!(
+            "{label}.country '{country}' must be a 2-letter ISO code (e.g. US, DE). \
+             See `arky platform countries`"
+        )));
+    }
+    Ok(())
 }
 
-/// Merge base object with data overlay
-pub fn merge_data(base: &mut Value, overlay: Value) {
-    if let (Value::Object(base_map), Value::Object(overlay_map)) = (base, overlay) {
-        for (k, v) in overlay_map {
-            base_map.insert(k, v);
+/// Save a quote's request payload and result under ~/.arky/cache/<name>.json
+/// so a later checkout can reuse the same items (`--save-last` / `--use-last`)
+pub fn save_last_quote(name: &str, request: &Value, result: &Value) -> Result<()> {
+    crate::cache::store(name, &serde_json::json!({ "request": request, "result": result }))
+}
+
+/// Load the request payload saved by `save_last_quote`
+pub fn load_last_quote(name: &str, quote_cmd: &str) -> Result<Value> {
+    crate::cache::load(name, None)
+        .and_then(|saved| saved.get("request").cloned())
+        .ok_or_else(|| {
+            CliError::InvalidInput(format!(
+                "No saved quote found. Run `arky {quote_cmd} --save-last --data ...` first"
+            ))
+        })
+}
+
+/// Give every block (including nested list/map sub-blocks) a fresh UUID
+pub fn regenerate_block_ids(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(regenerate_block_ids),
+        Value::Object(obj) => {
+            if obj.contains_key("type") && obj.contains_key("key") && obj.contains_key("id") {
+                obj.insert("id".into(), Value::String(uuid::Uuid::new_v4().to_string()));
+            }
+            obj.values_mut().for_each(regenerate_block_ids);
         }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_fill_missing_fields() {
+        let mut body = json!({"id": "n1", "status": "active"});
+        let current = json!({"key": "home", "status": "draft", "blocks": [{"key": "title"}]});
+        let kept = fill_missing_fields(&mut body, &current, NODE_REQUIRED_FIELDS);
+        assert_eq!(kept, vec!["key", "blocks"]);
+        assert_eq!(body["status"], "active");
+        assert_eq!(body["blocks"][0]["key"], "title");
+        assert!(body.get("slug").is_none());
+    }
+
+    #[test]
+    fn test_parse_records_ndjson() {
+        let records = parse_records(Some("{\"key\": \"a\"}\n{\"key\": \"b\"}\n")).unwrap();
+        assert_eq!(records, vec![json!({"key": "a"}), json!({"key": "b"})]);
+        assert_eq!(parse_records(Some("{\"a\": 1}")).unwrap().len(), 1);
+        assert!(parse_records(Some("{\"a\": 1}\n{oops")).is_err());
+        assert!(parse_records(Some("  ")).is_err());
+    }
+
+    #[test]
+    fn test_created_id() {
+        assert_eq!(created_id(&json!({"id": "n1"})), Some("n1"));
+        assert_eq!(created_id(&json!({"data": {"id": "n2"}})), Some("n2"));
+        assert_eq!(created_id(&json!({"id": "", "data": {"id": "n3"}})), Some("n3"));
+        assert_eq!(created_id(&json!({"success": true})), None);
+    }
+
+    #[test]
+    fn test_list_args_unbounded_cap() {
+        let list = |limit: Option<u32>, all: bool| ListArgs {
+            limit,
+            cursor: None,
+            all,
+            page_size: 100,
+            count: false,
+            concurrency: DEFAULT_CONCURRENCY,
+        };
+        assert_eq!(list(None, false).unbounded_cap(), None);
+        assert_eq!(list(Some(50), false).unbounded_cap(), None);
+        assert_eq!(list(Some(0), false).unbounded_cap(), Some(FETCH_ALL_CAP));
+        assert_eq!(list(None, true).unbounded_cap(), Some(FETCH_ALL_CAP));
+        assert_eq!(list(Some(250), true).unbounded_cap(), Some(250));
+        assert_eq!(list(None, false).with_default_limit(100).limit, Some(100));
+        assert_eq!(list(None, true).with_default_limit(100).unbounded_cap(), Some(FETCH_ALL_CAP));
+    }
+
+    #[test]
+    fn test_server_total() {
+        assert_eq!(server_total(&json!({"data": [], "total": 42})), Some(42));
+        assert_eq!(server_total(&json!({"count": 7})), Some(7));
+        assert_eq!(server_total(&json!({"data": [{}], "cursor": "c"})), None);
+    }
+
+    #[test]
+    fn test_grep_items() {
+        let mut events = json!(["order.paid", "order.refunded", "booking.confirmed"]);
+        grep_items(&mut events, "^order\\.");
+        assert_eq!(events, json!(["order.paid", "order.refunded"]));
+
+        let mut currencies = json!({
+            "items": [{"code": "USD", "name": "US Dollar"}, {"code": "EUR"}]
+        });
+        grep_items(&mut currencies, "dollar");
+        assert_eq!(currencies["items"], json!([{"code": "USD", "name": "US Dollar"}]));
+
+        // Invalid regex falls back to a literal substring
+        let mut odd = json!(["a(b", "ab"]);
+        grep_items(&mut odd, "a(");
+        assert_eq!(odd, json!(["a(b"]));
+    }
+
+    #[test]
+    fn test_json_diff() {
+        let old = json!({
+            "status": "draft",
+            "slug": {"en": "a"},
+            "blocks": [
+                {"key": "title", "value": {"en": "Old"}},
+                {"key": "body", "value": {"en": "x"}},
+            ],
+        });
+        let new = json!({
+            "status": "active",
+            "slug": {"en": "a", "es": "a"},
+            "blocks": [
+                {"key": "title", "value": {"en": "New"}},
+                {"key": "hero", "value": null},
+            ],
+        });
+        let diff = json_diff(&old, &new);
+        let ops: Vec<(&str, &str)> = diff
+            .iter()
+            .map(|d| (d["op"].as_str().unwrap(), d["path"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                ("changed", "blocks[title].value.en"),
+                ("removed", "blocks[body]"),
+                ("added", "blocks[hero]"),
+                ("added", "slug.es"),
+                ("changed", "status"),
+            ]
+        );
+        assert_eq!(diff[4]["from"], "draft");
+        assert!(json_diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_json_diff_plain_arrays_compare_whole() {
+        let diff = json_diff(&json!({"ids": ["a", "b"]}), &json!({"ids": ["b", "a"]}));
+        assert_eq!(
+            diff,
+            vec![json!({"op": "changed", "path": "ids", "from": ["a", "b"], "to": ["b", "a"]})]
+        );
+    }
+
+    #[test]
+    fn test_expand_env() {
+        let lookup = |name: &str| match name {
+            "NAME" => Some("Shop".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+        assert_eq!(
+            expand_env(r#"{"name": "${NAME}", "n": ${COUNT:-3}}"#, lookup).unwrap(),
+            r#"{"name": "Shop", "n": 3}"#
+        );
+        assert_eq!(expand_env("${EMPTY:-x} ${EMPTY}", lookup).unwrap(), "x ");
+        assert_eq!(expand_env("$5 $${NAME}", lookup).unwrap(), "$5 ${NAME}");
+        assert!(expand_env("${MISSING}", lookup).unwrap_err().contains("MISSING"));
+        assert!(expand_env("${NAME", lookup).is_err());
+        assert!(expand_env("${A B}", lookup).is_err());
+    }
+
+    #[test]
+    fn test_next_cursor() {
+        assert_eq!(next_cursor(&json!({"data": [], "cursor": "c1"})).as_deref(), Some("c1"));
+        assert_eq!(next_cursor(&json!({"items": [], "nextCursor": "c2"})).as_deref(), Some("c2"));
+        assert_eq!(next_cursor(&json!({"meta": {"cursor": "c3"}})).as_deref(), Some("c3"));
+        assert_eq!(
+            next_cursor(&json!({"pagination": {"nextCursor": "c4"}})).as_deref(),
+            Some("c4")
+        );
+        assert_eq!(next_cursor(&json!({"cursor": "", "nextCursor": "c5"})).as_deref(), Some("c5"));
+        assert_eq!(next_cursor(&json!({"cursor": null})), None);
+        assert_eq!(next_cursor(&json!([{"id": "a"}])), None);
+    }
+
+    #[test]
+    fn test_page_items() {
+        let item = json!({"id": "a"});
+        assert_eq!(page_items(&json!({"data": [item]})), Some(&vec![item.clone()]));
+        assert_eq!(page_items(&json!({"items": [item]})), Some(&vec![item.clone()]));
+        assert_eq!(page_items(&json!([item])), Some(&vec![item.clone()]));
+        assert_eq!(page_items(&json!({"id": "a"})), None);
+    }
+
+    #[test]
+    fn test_validate_address() {
+        let ok = json!({
+            "name": "Home", "street1": "456 Oak Ave", "city": "LA",
+            "postalCode": "90001", "country": "US"
+        });
+        assert!(validate_address(&ok, "Address").is_ok());
+
+        let err = validate_address(&json!({"name": "Home", "city": " "}), "toAddress")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("toAddress is missing required field(s): street1, city, postalCode"));
+        assert!(validate_address(&json!(["not", "an", "object"]), "Address").is_err());
+
+        let mut full_name = ok.clone();
+        full_name["country"] = json!("United States");
+        let err = validate_address(&full_name, "fromAddress").unwrap_err().to_string();
+        assert!(err.contains("fromAddress.country 'United States'"));
+    }
+
+    #[test]
+    fn test_merge_data_rejects_non_objects() {
+        let mut body = json!({"key": "shop"});
+        merge_data(&mut body, json!({"status": "active"})).unwrap();
+        assert_eq!(body, json!({"key": "shop", "status": "active"}));
+
+        let err = merge_data(&mut body, json!([1, 2, 3])).unwrap_err();
+        assert!(err.to_string().contains("--data must be a JSON object, got an array"));
+        assert!(merge_data(&mut body, json!("text")).is_err());
+        assert_eq!(body, json!({"key": "shop", "status": "active"}));
+    }
+
+    #[test]
+    fn test_merge_data_auto_set_fields() {
+        let mut body = json!({"key": "shop", "id": "n1", "businessId": "biz_1"});
+        let overlay = json!({"key": "other", "id": "n2", "businessId": "biz_2", "status": "draft"});
+        merge_data(&mut body, overlay).unwrap();
+        assert_eq!(
+            body,
+            json!({"key": "shop", "id": "n1", "businessId": "biz_2", "status": "draft"})
+        );
+
+        let mut body = json!({});
+        merge_data(&mut body, json!({"key": "from-data"})).unwrap();
+        assert_eq!(body["key"], "from-data");
+    }
+
+    #[test]
+    fn test_capped_page() {
+        let page = json!({"data": [{"id": "a"}, {"id": "b"}], "cursor": "c2"});
+        assert_eq!(capped_page(&page, 1000), Some(2));
+        assert_eq!(capped_page(&page, 2), None);
+        assert_eq!(capped_page(&json!({"data": [{"id": "a"}]}), 1000), None);
+    }
+
+    #[test]
+    fn test_delete_summary() {
+        let node = json!({
+            "id": "n1", "key": "home", "status": "active", "slug": {"en": "home"},
+            "blocks": [{"key": "title", "value": {"sr": "Početna", "en": "Home"}}]
+        });
+        assert_eq!(
+            delete_summary(&node),
+            json!({"id": "n1", "key": "home", "status": "active", "title": "Home"})
+        );
+        let audience = json!({"id": "a1", "key": "vip", "name": "VIP members"});
+        assert_eq!(delete_summary(&audience)["title"], "VIP members");
+        assert!(delete_summary(&json!({"id": "p1"})).get("title").is_none());
+    }
+
+    #[test]
+    fn test_is_key_conflict() {
+        let api = |status, field: &str, error: &str| CliError::Api {
+            status,
+            message: "Request failed".into(),
+            error: None,
+            validation_errors: vec![crate::error::ValidationError {
+                field: field.into(),
+                error: error.into(),
+            }],
+            request_id: None,
+        };
+        assert!(is_key_conflict(&api(409, "", "")));
+        assert!(is_key_conflict(&api(400, "key", "Key already exists")));
+        assert!(!is_key_conflict(&api(400, "key", "Invalid characters")));
+        assert!(!is_key_conflict(&api(400, "slug", "Slug already exists")));
+        assert!(!is_key_conflict(&CliError::Config("x".into())));
+    }
+
+    #[test]
+    fn test_ids_with_key() {
+        let page = json!({"data": [
+            {"id": "n1", "key": "blog"},
+            {"id": "n2", "key": "blog-archive"},
+            {"id": "n3", "key": "blog"},
+            {"key": "blog"},
+        ]});
+        assert_eq!(ids_with_key(&page, "blog"), vec!["n1", "n3"]);
+        assert_eq!(ids_with_key(&page, "blog-archive"), vec!["n2"]);
+        assert!(ids_with_key(&page, "news").is_empty());
+    }
+
+    #[test]
+    fn test_parse_ids() {
+        assert_eq!(parse_ids("n1\n\n  n2 \n").unwrap(), vec!["n1", "n2"]);
+        assert_eq!(parse_ids(r#"["n1", {"id": "n2", "key": "x"}]"#).unwrap(), vec!["n1", "n2"]);
+        assert!(parse_ids("[1]").is_err());
+        assert!(parse_ids(" \n").is_err());
+    }
+
+    #[test]
+    fn test_set_price() {
+        let mut prices = json!([{"market": "us", "currency": "USD", "amount": 1999}]);
+        set_price(&mut prices, "US", "usd", 2499);
+        set_price(&mut prices, "eu", "eur", 2299);
+        assert_eq!(
+            prices,
+            json!([
+                {"market": "us", "currency": "USD", "amount": 2499},
+                {"market": "eu", "currency": "eur", "amount": 2299}
+            ])
+        );
+        let mut missing = Value::Null;
+        set_price(&mut missing, "us", "usd", 100);
+        assert_eq!(missing, json!([{"market": "us", "currency": "usd", "amount": 100}]));
+    }
+
+    #[test]
+    fn test_find_entry() {
+        let mut product = json!({"variants": [{"key": "small"}, {"key": "large"}]});
+        let entry = find_entry(&mut product, "variants", "key", "large").unwrap();
+        entry["sku"] = json!("L");
+        assert_eq!(product["variants"][1]["sku"], "L");
+        let err = find_entry(&mut product, "variants", "key", "medium").unwrap_err();
+        assert!(err.to_string().contains("found: small, large"));
+        assert!(find_entry(&mut json!({}), "variants", "key", "x").is_err());
+    }
+
+    #[test]
+    fn test_deleted_ignores_missing() {
+        let not_found = || {
+            Err(CliError::Api {
+                status: 404,
+                message: "Not found".into(),
+                error: None,
+                validation_errors: vec![],
+                request_id: None,
+            })
+        };
+        assert!(deleted(Ok(json!({})), "node", false).unwrap());
+        assert!(!deleted(not_found(), "node", true).unwrap());
+        assert!(deleted(not_found(), "node", false).is_err());
+        let server_error = Err(CliError::Config("boom".into()));
+        assert!(deleted(server_error, "node", true).is_err());
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let body = json!({"id": "n1", "key": "page", "blockz": [], "createdAt": 1});
+        assert_eq!(unknown_fields(&body, &["key", "blocks"]), vec!["blockz"]);
+        assert!(unknown_fields(&json!({"key": "page"}), &["key"]).is_empty());
+        assert!(check_known_fields(&body, &["key"], "node").is_ok());
+    }
+
+    #[test]
+    fn test_resolve_last_id_leaves_other_args() {
+        // No @last token: nothing is read from the cache
+        let args: Vec<String> = ["arky", "node", "update", "n1", "--data", "@last"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(resolve_last_id(args.clone()).unwrap(), args);
+        let args = vec!["arky".to_string(), "--data=@last".to_string()];
+        assert_eq!(resolve_last_id(args.clone()).unwrap(), args);
+        let args: Vec<String> = ["arky", "order", "quote", "--from-cart", "@last"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(resolve_last_id(args.clone()).unwrap(), args);
+        let args = vec!["arky".to_string(), "--from-cart=@last".to_string()];
+        assert_eq!(resolve_last_id(args.clone()).unwrap(), args);
+    }
+
+    #[test]
+    fn test_keyed_records() {
+        let one = keyed_records(Some("k".into()), vec![json!({"status": "draft"})]).unwrap();
+        assert_eq!(one, vec![json!({"key": "k", "status": "draft"})]);
+        let many = keyed_records(None, vec![json!({"key": "a"}), json!({"key": "b"})]).unwrap();
+        assert_eq!(many.len(), 2);
+        assert!(keyed_records(Some("k".into()), vec![json!({}), json!({})]).is_err());
+        assert!(keyed_records(None, vec![json!({"status": "draft"})]).is_err());
+    }
+
+    #[test]
+    fn test_parse_time_arg() {
+        assert_eq!(parse_time_arg("1700000000").unwrap(), 1_700_000_000);
+        assert_eq!(parse_time_arg("1970-01-01").unwrap(), 0);
+        assert_eq!(parse_time_arg("2024-03-01").unwrap(), 1_709_251_200);
+        assert!(parse_time_arg("2024-13-01").is_err());
+        assert!(parse_time_arg("2025-02-31").is_err());
+        assert!(parse_time_arg("2025-02-29").is_err());
+        assert!(parse_time_arg("2025-04-31").is_err());
+        assert_eq!(parse_time_arg("2024-02-29").unwrap(), 1_709_164_800);
+        assert!(parse_time_arg("1900-02-29").is_err());
+        assert!(parse_time_arg("2000-02-29").is_ok());
+        assert!(parse_time_arg("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_parse_time_relative() {
+        let now = 1_700_000_000;
+        assert_eq!(parse_time_at("now", now).unwrap(), now);
+        assert_eq!(parse_time_at("7d", now).unwrap(), now - 7 * 86_400);
+        assert_eq!(parse_time_at("12h", now).unwrap(), now - 12 * 3_600);
+        assert_eq!(parse_time_at("30m", now).unwrap(), now - 1_800);
+        assert_eq!(parse_time_at("45s", now).unwrap(), now - 45);
+        assert_eq!(parse_time_at("2w", now).unwrap(), now - 14 * 86_400);
+        assert_eq!(parse_time_at("+3d", now).unwrap(), now + 3 * 86_400);
+        assert_eq!(parse_time_at("-7d", now).unwrap(), now - 7 * 86_400);
+        assert!(parse_time_at("+-3d", now).is_err());
+        assert!(parse_time_at("--3d", now).is_err());
+        assert!(parse_time_at("7y", now).is_err());
+        assert!(parse_time_at("d", now).is_err());
+    }
+
+    #[test]
+    fn test_regenerate_block_ids_nested() {
+        let mut blocks = json!([
+            {"type": "text", "id": "b1", "key": "title", "properties": {}, "value": "Hi"},
+            {"type": "list", "id": "b2", "key": "items", "properties": {}, "value": [
+                {"type": "text", "id": "b3", "key": "item", "properties": {}, "value": "x"}
+            ]}
+        ]);
+        regenerate_block_ids(&mut blocks);
+        assert_ne!(blocks[0]["id"], "b1");
+        assert_ne!(blocks[1]["id"], "b2");
+        assert_ne!(blocks[1]["value"][0]["id"], "b3");
+        assert_eq!(blocks[0]["value"], "Hi");
     }
 }
@@ -1,9 +1,11 @@
 use crate::client::ArkyClient;
+use crate::commands::{print_list, ListArgs};
 use crate::error::Result;
 use crate::output::Format;
 use clap::Subcommand;
 
 #[derive(Subcommand, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum NetworkCommand {
     /// Search services across a network
     #[command(name = "search-services", long_about = "Search for services across a network.\n\n\
@@ -18,10 +20,8 @@ pub enum NetworkCommand {
         network_key: String,
         #[arg(long)]
         query: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
         #[arg(long)]
         statuses: Option<String>,
         #[arg(long)]
@@ -43,10 +43,8 @@ pub enum NetworkCommand {
         network_key: String,
         #[arg(long)]
         query: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
         #[arg(long)]
         statuses: Option<String>,
         #[arg(long)]
@@ -71,10 +69,8 @@ pub enum NetworkCommand {
         network_key: String,
         #[arg(long)]
         query: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
         #[arg(long)]
         statuses: Option<String>,
         #[arg(long)]
@@ -89,19 +85,15 @@ pub async fn handle(cmd: NetworkCommand, client: &ArkyClient, format: &Format) -
         NetworkCommand::SearchServices {
             network_key,
             query,
-            limit,
-            cursor,
+            list,
             statuses,
             sort_field,
             sort_direction,
         } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             if let Some(ref s) = statuses {
                 params.push(("statuses", s.clone()));
             }
@@ -113,32 +105,29 @@ pub async fn handle(cmd: NetworkCommand, client: &ArkyClient, format: &Format) -
             }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(
-                    &format!("/v1/networks/{network_key}/services"),
-                    &params_ref,
-                )
-                .await?;
-            crate::output::print_output(&result, format);
+            print_list(
+                client,
+                &format!("/v1/networks/{network_key}/services"),
+                &params_ref,
+                &list,
+                format,
+            )
+            .await?;
         }
         NetworkCommand::SearchProducts {
             network_key,
             query,
-            limit,
-            cursor,
+            list,
             statuses,
             sort_field,
             sort_direction,
             price_from,
             price_to,
         } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             if let Some(ref s) = statuses {
                 params.push(("statuses", s.clone()));
             }
@@ -156,30 +145,27 @@ pub async fn handle(cmd: NetworkCommand, client: &ArkyClient, format: &Format) -
             }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(
-                    &format!("/v1/networks/{network_key}/products"),
-                    &params_ref,
-                )
-                .await?;
-            crate::output::print_output(&result, format);
+            print_list(
+                client,
+                &format!("/v1/networks/{network_key}/products"),
+                &params_ref,
+                &list,
+                format,
+            )
+            .await?;
         }
         NetworkCommand::SearchProviders {
             network_key,
             query,
-            limit,
-            cursor,
+            list,
             statuses,
             sort_field,
             sort_direction,
         } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             if let Some(ref s) = statuses {
                 params.push(("statuses", s.clone()));
             }
@@ -191,13 +177,14 @@ pub async fn handle(cmd: NetworkCommand, client: &ArkyClient, format: &Format) -
             }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(
-                    &format!("/v1/networks/{network_key}/providers"),
-                    &params_ref,
-                )
-                .await?;
-            crate::output::print_output(&result, format);
+            print_list(
+                client,
+                &format!("/v1/networks/{network_key}/providers"),
+                &params_ref,
+                &list,
+                format,
+            )
+            .await?;
         }
     }
     Ok(())
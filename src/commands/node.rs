@@ -1,9 +1,20 @@
 use crate::client::ArkyClient;
-use crate::commands::{merge_data, parse_data};
-use crate::error::Result;
+use crate::commands::media::resolve_media_urls;
+use crate::commands::{
+    check_known_fields, confirm, confirm_delete, create_or_update, delete_entity, delete_from_stdin,
+    fetch_list, fill_missing_fields, json_diff, keyed_records, merge_data, note_failed_record,
+    parse_data, parse_records, preserve_required_fields, print_created, print_example, print_list,
+    resolve_key, set_status, DeleteArgs, Example, ListArgs, NODE_REQUIRED_FIELDS,
+};
+use crate::error::{CliError, Result};
 use crate::output::Format;
 use clap::Subcommand;
-use serde_json::json;
+use serde_json::{json, Value};
+
+/// Top-level node fields accepted in --data (checked with --strict)
+const NODE_FIELDS: &[&str] = &[
+    "key", "slug", "status", "writeAccess", "audienceIds", "blocks", "parentId", "type",
+];
 
 #[derive(Subcommand, Debug)]
 pub enum NodeCommand {
@@ -12,7 +23,11 @@ pub enum NodeCommand {
         Accepts node ID, slug, or key as the identifier.\n\n\
         Example:\n\
         arky node get NODE_ID\n\
-        arky node get my-blog-post\n\n\
+        arky node get my-blog-post\n\
+        arky node get NODE_ID --resolve-media\n\n\
+        --resolve-media fetches every media referenced by relationship_media\n\
+        blocks (a few at a time) and adds {\"mediaUrls\": {\"MEDIA_ID\": \"https://...\"}}\n\
+        to each such block, using the original resolution's URL.\n\n\
         Response shape:\n\
         {\"id\": \"...\", \"key\": \"my-post\", \"type\": \"blog\", \"status\": \"active\",\n\
          \"blocks\": [\n\
@@ -23,6 +38,8 @@ pub enum NodeCommand {
     Get {
         /// Node ID, slug, or key
         id: String,
+        #[arg(long, help = "Add each relationship_media block's media URLs")]
+        resolve_media: bool,
     },
     /// List content nodes
     #[command(long_about = "List content nodes with optional filters.\n\n\
@@ -35,7 +52,11 @@ pub enum NodeCommand {
         arky node list --type blog --limit 10\n\
         arky node list --query \"hello\" --statuses active\n\
         arky node list --parent-id PARENT_NODE_ID\n\
-        arky node list --sort-field createdAt --sort-direction desc\n\n\
+        arky node list --sort-field createdAt --sort-direction desc\n\
+        arky node list --all --has-block hero_image\n\
+        arky node list --all --block category=news\n\n\
+        Block filters (--has-block, --block) run client-side over the fetched\n\
+        page(s), so combine them with --all to search every node.\n\n\
         Response shape:\n\
         {\"data\": [{\"id\": \"...\", \"key\": \"...\", \"type\": \"...\", \"status\": \"...\",\n\
           \"blocks\": [...]}], \"cursor\": \"next_page_cursor\"}")]
@@ -48,16 +69,22 @@ pub enum NodeCommand {
         key: Option<String>,
         #[arg(long)]
         parent_id: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
         #[arg(long, help = "Comma-separated: draft,active,archived")]
         statuses: Option<String>,
         #[arg(long)]
         sort_field: Option<String>,
         #[arg(long)]
         sort_direction: Option<String>,
+        #[arg(long, value_name = "KEY", help = "Keep nodes that have this block (repeatable)")]
+        has_block: Vec<String>,
+        #[arg(
+            long = "block",
+            value_name = "KEY=VALUE",
+            help = "Keep nodes whose block KEY has VALUE, any locale (repeatable)"
+        )]
+        block_filters: Vec<String>,
     },
     /// Create a content node
     #[command(long_about = "Create a content node with blocks.\n\n\
@@ -70,6 +97,7 @@ pub enum NodeCommand {
       blocks        Array of content blocks (see block types below)\n\n\
     Optional:\n\
       parentId   ID of parent node (for hierarchical content)\n\
+                 --parent-id ID sets it, or --parent KEY looks the parent up by key\n\
       status     \"draft\" (default) | \"active\" | \"archived\"\n\n\
     Block fields (ALL required on each block):\n\
       type        Block type (see types below)\n\
@@ -103,14 +131,31 @@ pub enum NodeCommand {
         {\"type\": \"number\", \"id\": \"b3\", \"key\": \"count\", \"properties\": {}, \"value\": 42},\n\
         {\"type\": \"boolean\", \"id\": \"b4\", \"key\": \"visible\", \"properties\": {}, \"value\": true}\n\
       ]\n\
-    }'")]
+    }'\n\n\
+    Batch create (NDJSON):\n\
+      Several newline-delimited JSON objects in --data create one node each,\n\
+      in order. Omit KEY and give each record its own \"key\".\n\
+      cat nodes.ndjson | arky node create --data -")]
     Create {
-        /// Node key (unique within business, URL-safe)
-        key: String,
+        /// Node key (unique within business, URL-safe); omit for multi-record --data
+        key: Option<String>,
         #[arg(long)]
         parent_id: Option<String>,
+        #[arg(
+            long,
+            value_name = "KEY",
+            conflicts_with = "parent_id",
+            help = "Parent node key, resolved to its ID"
+        )]
+        parent: Option<String>,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Print only the created ID, e.g. for ID=$(arky ... --id-only)")]
+        id_only: bool,
+        #[arg(long, help = "If KEY is taken, update that node instead of failing")]
+        upsert: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Update a content node
     #[command(long_about = "Update a content node.\n\n\
@@ -128,6 +173,17 @@ pub enum NodeCommand {
     Each block needs: type, id, key, properties, value (same as create).\n\
     Block types: localized_text, markdown, number, boolean, text, list, map,\n\
     relationship_entry, relationship_media, geo_location\n\n\
+    Preserved fields:\n\
+      key, slug, status, writeAccess, audienceIds and blocks\n\
+      are copied from the current node when missing from --data.\n\
+      Use --force to send the payload as-is.\n\n\
+    Partial updates:\n\
+      --patch sends only the given fields via PATCH (if the server supports it).\n\
+      arky node update NODE_ID --patch --data '{\"status\": \"active\"}'\n\n\
+    Preview:\n\
+      --diff shows what would change (see `arky node diff`) and asks before\n\
+      sending. --yes skips the question, e.g. in scripts.\n\
+      arky node update NODE_ID --diff --data @page.json\n\n\
     Working example (from integration tests):\n\
     arky node update NODE_ID --data '{\n\
       \"key\": \"my-page\",\n\
@@ -141,15 +197,80 @@ pub enum NodeCommand {
       ]\n\
     }'")]
     Update {
+        /// Node ID
+        #[arg(required_unless_present = "example", default_value = "")]
+        id: String,
+        #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
+        data: Option<String>,
+        #[arg(long, help = "Send only the given fields via PATCH instead of a full PUT")]
+        patch: bool,
+        #[arg(long, help = "Don't fill in required fields missing from --data")]
+        force: bool,
+        #[arg(long, help = "Show what would change and ask before updating")]
+        diff: bool,
+        #[arg(long, short = 'y', requires = "diff", help = "Skip the --diff confirmation")]
+        yes: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
+    },
+    /// Show what an update would change
+    #[command(long_about = "Compare a node with the body `arky node update` would send.\n\n\
+        Fetches the node, builds the update body from --data (keeping required\n\
+        fields that are missing, as update does) and lists added, removed and\n\
+        changed paths. Blocks are matched by key. Fields not in the update body\n\
+        (timestamps and other server-managed data) are not compared.\n\n\
+        Example:\n\
+        arky node diff NODE_ID --data @page.json\n\n\
+        Response: [{\"op\": \"changed\", \"path\": \"blocks[title].value.en\",\n\
+                    \"from\": \"Old\", \"to\": \"New\"}, ...]")]
+    Diff {
         /// Node ID
         id: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
     },
+    /// Set a node's status to active
+    #[command(long_about = "Publish a node (status \"active\").\n\n\
+        Fetches the current node, changes only the status, and PUTs the full\n\
+        object back, so blocks, slug and other required fields are kept.\n\n\
+        Example:\n\
+        arky node publish NODE_ID")]
+    Publish {
+        /// Node ID
+        id: String,
+    },
+    /// Set a node's status to archived
+    #[command(long_about = "Archive a node (status \"archived\"), keeping all other fields.\n\n\
+        Example:\n\
+        arky node archive NODE_ID")]
+    Archive {
+        /// Node ID
+        id: String,
+    },
+    /// Set a node's status to draft
+    #[command(long_about = "Move a node back to draft (status \"draft\"), keeping all other fields.\n\n\
+        Example:\n\
+        arky node draft NODE_ID")]
+    Draft {
+        /// Node ID
+        id: String,
+    },
     /// Delete a content node
+    #[command(long_about = "Delete a content node by ID.\n\n\
+        --show fetches it first, prints its id/key/status/title and asks before\n\
+        deleting; add --yes to skip the question.\n\n\
+        --stdin deletes every ID piped in (one per line, or a JSON array of IDs or\n\
+        objects with an id), reporting each and continuing past failures unless\n\
+        --stop-on-error is given.\n\n\
+        Examples:\n\
+        arky node delete NODE_ID --show\n\
+        arky node list --all --pointer /data | arky node delete --stdin")]
     Delete {
         /// Node ID
+        #[arg(required_unless_present = "stdin", default_value = "")]
         id: String,
+        #[command(flatten)]
+        delete: DeleteArgs,
     },
     /// Get children of a content node
     #[command(long_about = "List child nodes of a parent node.\n\n\
@@ -159,35 +280,45 @@ pub enum NodeCommand {
     Children {
         /// Parent node ID
         id: String,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
     },
 }
 
 pub async fn handle(cmd: NodeCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    if cmd.is_example() {
+        return print_example(&example());
+    }
     let biz_id = client.require_business_id()?;
 
     match cmd {
-        NodeCommand::Get { id } => {
-            let result = client
+        NodeCommand::Get { id, resolve_media } => {
+            let mut result = client
                 .get(&format!("/v1/businesses/{biz_id}/nodes/{id}"), &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            if resolve_media {
+                resolve_media_urls(client, &mut result).await?;
+            }
+            crate::output::print_output(&result, format)?;
         }
         NodeCommand::List {
             r#type,
             query,
             key,
             parent_id,
-            limit,
-            cursor,
+            list,
             statuses,
             sort_field,
             sort_direction,
+            has_block,
+            block_filters,
         } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+            let block_filters = block_filters
+                .iter()
+                .map(|f| parse_block_filter(f))
+                .collect::<Result<Vec<_>>>()?;
+            let filtering = !has_block.is_empty() || !block_filters.is_empty();
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref t) = r#type {
                 params.push(("type", t.clone()));
             }
@@ -200,9 +331,6 @@ pub async fn handle(cmd: NodeCommand, client: &ArkyClient, format: &Format) -> R
             if let Some(ref p) = parent_id {
                 params.push(("parentId", p.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             if let Some(ref s) = statuses {
                 params.push(("statuses", s.clone()));
             }
@@ -214,57 +342,281 @@ pub async fn handle(cmd: NodeCommand, client: &ArkyClient, format: &Format) -> R
             }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(&format!("/v1/businesses/{biz_id}/nodes"), &params_ref)
+            if !filtering {
+                print_list(
+                    client,
+                    &format!("/v1/businesses/{biz_id}/nodes"),
+                    &params_ref,
+                    &list,
+                    format,
+                )
                 .await?;
-            crate::output::print_output(&result, format);
+                return Ok(());
+            }
+
+            // A server-side count can't see the block filters, so count fetched matches
+            let mut fetch = list.clone();
+            if list.count {
+                fetch.count = false;
+                fetch.all = true;
+            }
+            let mut result = fetch_list(
+                client,
+                &format!("/v1/businesses/{biz_id}/nodes"),
+                &params_ref,
+                &fetch,
+            )
+            .await?;
+            if let Some(nodes) = result["data"].as_array_mut() {
+                nodes.retain(|node| node_matches(node, &has_block, &block_filters));
+            }
+            if list.count {
+                let matched = result["data"].as_array().map_or(0, Vec::len);
+                crate::output::print_output(&json!(matched), format)?;
+            } else {
+                crate::output::print_output(&result, format)?;
+            }
         }
         NodeCommand::Create {
             key,
             parent_id,
+            parent,
             data,
+            id_only,
+            upsert,
+            ..
         } => {
-            let mut body = json!({ "key": key });
-            if let Some(pid) = parent_id {
-                body["parentId"] = json!(pid);
+            let bodies = keyed_records(key, parse_records(data.as_deref())?)?;
+            for body in &bodies {
+                check_known_fields(body, NODE_FIELDS, "node")?;
+            }
+            let path = format!("/v1/businesses/{biz_id}/nodes");
+            let parent_id = match parent {
+                Some(key) => Some(resolve_key(client, &path, "node", &key).await?),
+                None => parent_id,
+            };
+            let total = bodies.len();
+            for (i, mut body) in bodies.into_iter().enumerate() {
+                if let Some(ref pid) = parent_id {
+                    if body.get("parentId").is_none() {
+                        body["parentId"] = json!(pid);
+                    }
+                }
+                let result = create_or_update(client, &path, "node", &body, upsert)
+                    .await
+                    .inspect_err(|_| note_failed_record(i, total))?;
+                print_created(&result, "node", id_only, format)?;
             }
+        }
+        NodeCommand::Update {
+            id,
+            data,
+            patch,
+            force,
+            diff,
+            yes,
+            ..
+        } => {
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
-            let result = client
-                .post(&format!("/v1/businesses/{biz_id}/nodes"), &body)
-                .await?;
-            crate::output::print_output(&result, format);
+            check_known_fields(&overlay, NODE_FIELDS, "node")?;
+            let path = format!("/v1/businesses/{biz_id}/nodes/{id}");
+            let mut body = if patch {
+                overlay
+            } else {
+                let mut body = json!({ "id": id });
+                merge_data(&mut body, overlay)?;
+                body
+            };
+            if diff {
+                let current = client.get(&path, &[]).await?;
+                if !patch && !force {
+                    fill_missing_fields(&mut body, &current, NODE_REQUIRED_FIELDS);
+                }
+                let changes = update_diff(&current, &body);
+                crate::output::print_preview(&json!(changes), format);
+                if changes.is_empty() {
+                    crate::output::print_success("No changes, node not updated");
+                    return Ok(());
+                }
+                if !yes && !confirm("Apply these changes?")? {
+                    crate::output::print_warning("Update cancelled");
+                    return Ok(());
+                }
+            } else if !patch && !force {
+                preserve_required_fields(client, &path, &mut body, NODE_REQUIRED_FIELDS).await?;
+            }
+            let result = if patch {
+                client.patch(&path, &body).await?
+            } else {
+                client.put(&path, &body).await?
+            };
+            crate::output::print_output(&result, format)?;
         }
-        NodeCommand::Update { id, data } => {
+        NodeCommand::Diff { id, data } => {
+            let path = format!("/v1/businesses/{biz_id}/nodes/{id}");
+            let current = client.get(&path, &[]).await?;
             let mut body = json!({ "id": id });
-            let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
-            let result = client
-                .put(&format!("/v1/businesses/{biz_id}/nodes/{id}"), &body)
-                .await?;
-            crate::output::print_output(&result, format);
+            merge_data(&mut body, parse_data(data.as_deref())?)?;
+            fill_missing_fields(&mut body, &current, NODE_REQUIRED_FIELDS);
+            crate::output::print_output(&json!(update_diff(&current, &body)), format)?;
         }
-        NodeCommand::Delete { id } => {
-            let _ = client
-                .delete(&format!("/v1/businesses/{biz_id}/nodes/{id}"))
-                .await?;
-            crate::output::print_success("Node deleted");
+        NodeCommand::Publish { id } => {
+            let path = format!("/v1/businesses/{biz_id}/nodes/{id}");
+            let result = set_status(client, &path, "active").await?;
+            crate::output::print_output(&result, format)?;
+        }
+        NodeCommand::Archive { id } => {
+            let path = format!("/v1/businesses/{biz_id}/nodes/{id}");
+            let result = set_status(client, &path, "archived").await?;
+            crate::output::print_output(&result, format)?;
         }
-        NodeCommand::Children { id, limit, cursor } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
+        NodeCommand::Draft { id } => {
+            let path = format!("/v1/businesses/{biz_id}/nodes/{id}");
+            let result = set_status(client, &path, "draft").await?;
+            crate::output::print_output(&result, format)?;
+        }
+        NodeCommand::Delete { id, delete } => {
+            let collection = format!("/v1/businesses/{biz_id}/nodes");
+            if delete.stdin {
+                return delete_from_stdin(client, &collection, "node", &delete).await;
+            }
+            let path = format!("{collection}/{id}");
+            if !confirm_delete(client, &path, "node", &delete, format).await? {
+                return Ok(());
+            }
+            if delete_entity(client, &path, "node", delete.ignore_missing).await? {
+                crate::output::print_success("Node deleted");
             }
-            let params_ref: Vec<(&str, &str)> =
-                params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(
-                    &format!("/v1/businesses/{biz_id}/nodes/{id}/children"),
-                    &params_ref,
-                )
-                .await?;
-            crate::output::print_output(&result, format);
+        }
+        NodeCommand::Children { id, list } => {
+            print_list(
+                client,
+                &format!("/v1/businesses/{biz_id}/nodes/{id}/children"),
+                &[],
+                &list,
+                format,
+            )
+            .await?;
         }
     }
     Ok(())
 }
+
+fn example() -> Value {
+    json!({
+        "slug": { "en": "my-page" },
+        "status": "draft",
+        "writeAccess": "private",
+        "audienceIds": [],
+        "blocks": [
+            {
+                "type": "localized_text",
+                "id": "b1",
+                "key": "title",
+                "properties": {},
+                "value": { "en": "My Page" }
+            },
+            {
+                "type": "markdown",
+                "id": "b2",
+                "key": "body",
+                "properties": {},
+                "value": { "en": "# Hello" }
+            }
+        ]
+    })
+}
+
+/// Diff the current node against an update body. Only fields being sent are
+/// compared, so server-managed ones (timestamps, ...) don't show as removed.
+fn update_diff(current: &Value, body: &Value) -> Vec<Value> {
+    let sent: serde_json::Map<String, Value> = current
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(k, _)| body.get(k.as_str()).is_some())
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    json_diff(&Value::Object(sent), body)
+}
+
+fn parse_block_filter(filter: &str) -> Result<(String, String)> {
+    match filter.split_once('=') {
+        Some((k, v)) if !k.is_empty() => Ok((k.to_string(), v.to_string())),
+        _ => Err(CliError::InvalidInput(format!(
+            "Invalid --block '{filter}'. Expected KEY=VALUE"
+        ))),
+    }
+}
+
+/// Whether a node has every block in `has_block` and every `key=value` pair.
+/// Localized values match when any locale equals the text.
+fn node_matches(node: &Value, has_block: &[String], filters: &[(String, String)]) -> bool {
+    let blocks = node["blocks"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let find = |key: &str| blocks.iter().find(|b| b["key"] == key);
+    has_block.iter().all(|key| find(key).is_some())
+        && filters
+            .iter()
+            .all(|(key, want)| find(key).is_some_and(|b| value_matches(&b["value"], want)))
+}
+
+fn value_matches(value: &Value, want: &str) -> bool {
+    match value {
+        Value::String(s) => s == want,
+        Value::Number(_) | Value::Bool(_) => want.parse::<Value>().is_ok_and(|w| w == *value),
+        Value::Object(locales) => locales.values().any(|v| value_matches(v, want)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_has_required_fields() {
+        let example = example();
+        for field in NODE_REQUIRED_FIELDS.iter().filter(|f| **f != "key") {
+            assert!(example.get(field).is_some(), "missing {field}");
+        }
+        let fields = example.as_object().unwrap();
+        assert!(fields.keys().all(|k| NODE_FIELDS.contains(&k.as_str())));
+    }
+
+    #[test]
+    fn test_update_diff_ignores_unsent_fields() {
+        let current = json!({"id": "n1", "status": "draft", "createdAt": 1, "key": "page"});
+        let body = json!({"id": "n1", "status": "active", "key": "page"});
+        assert_eq!(
+            update_diff(&current, &body),
+            vec![json!({"op": "changed", "path": "status", "from": "draft", "to": "active"})]
+        );
+    }
+
+    #[test]
+    fn test_node_matches_block_filters() {
+        let node = json!({"blocks": [
+            {"key": "title", "value": {"en": "Hello", "sr": "Zdravo"}},
+            {"key": "category", "value": "news"},
+            {"key": "featured", "value": true}
+        ]});
+        let filter = |k: &str, v: &str| (k.to_string(), v.to_string());
+        assert!(node_matches(&node, &["title".into()], &[]));
+        assert!(!node_matches(&node, &["hero_image".into()], &[]));
+        assert!(node_matches(&node, &[], &[filter("category", "news")]));
+        assert!(node_matches(&node, &[], &[filter("title", "Zdravo")]));
+        assert!(node_matches(&node, &[], &[filter("featured", "true")]));
+        assert!(!node_matches(&node, &[], &[filter("category", "blog")]));
+        assert!(!node_matches(&json!({"id": "n1"}), &[], &[filter("category", "news")]));
+    }
+
+    #[test]
+    fn test_parse_block_filter() {
+        assert_eq!(
+            parse_block_filter("category=news").unwrap(),
+            ("category".to_string(), "news".to_string())
+        );
+        assert!(parse_block_filter("category").is_err());
+        assert!(parse_block_filter("=news").is_err());
+    }
+}
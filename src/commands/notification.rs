@@ -55,7 +55,7 @@ pub async fn handle(
             let result = client
                 .post("/v1/notifications/trigger", &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
     }
     Ok(())
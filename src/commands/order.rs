@@ -1,9 +1,20 @@
 use crate::client::ArkyClient;
-use crate::commands::{merge_data, parse_data};
-use crate::error::Result;
+use crate::commands::account::resolve_account_email;
+use crate::commands::business::BusinessConfigs;
+use crate::commands::{
+    fetch_all, load_last_quote, merge_data, parse_data, parse_time_arg, print_created,
+    print_example, print_list, save_last_quote, validate_address, write_out, Example, ListArgs,
+};
+use crate::error::{CliError, Result};
 use crate::output::Format;
 use clap::Subcommand;
-use serde_json::json;
+use reqwest::header::{HeaderName, HeaderValue};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Header sent by `order checkout --idempotent`
+const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
 
 #[derive(Subcommand, Debug)]
 pub enum OrderCommand {
@@ -23,23 +34,47 @@ pub enum OrderCommand {
         arky order list\n\
         arky order list --status paid --limit 10\n\
         arky order list --account-id ACC_ID\n\
-        arky order list --sort-field createdAt --sort-direction desc")]
+        arky order list --account-email jane@example.com\n\
+        arky order list --sort-field createdAt --sort-direction desc\n\
+        arky order list --status paid --count     # just the number\n\
+        arky order list --all                     # every page (up to 10000)\n\
+        arky order list --all --limit 500 --page-size 100")]
     List {
         #[arg(long, help = "Filter: pending, paid, shipped, delivered, cancelled, refunded")]
         status: Option<String>,
         #[arg(long)]
         query: Option<String>,
-        #[arg(long)]
+        #[arg(long, conflicts_with = "account_email")]
         account_id: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[arg(long, help = "Filter by customer email (resolved to an account ID)")]
+        account_email: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
         #[arg(long)]
         sort_field: Option<String>,
         #[arg(long)]
         sort_direction: Option<String>,
     },
+    /// Summarize orders: count, totals by currency, breakdown by status
+    #[command(long_about = "Aggregate all matching orders (every page) into a summary.\n\n\
+        Totals are summed per currency in minor units (e.g. 5000 = $50.00).\n\
+        --from/--to (or --since/--until) filter on createdAt and accept epoch\n\
+        seconds, YYYY-MM-DD, now, or an offset like 7d, 12h (ago) or +3d.\n\n\
+        Examples:\n\
+        arky order summary\n\
+        arky order summary --from 2025-01-01 --to 2025-02-01 --status paid\n\
+        arky order summary --since 7d\n\n\
+        Response shape:\n\
+        {\"count\": 42, \"totals\": {\"usd\": 125000},\n\
+         \"byStatus\": {\"paid\": {\"count\": 40, \"totals\": {\"usd\": 120000}}, ...}}")]
+    Summary {
+        #[arg(long, visible_alias = "since", help = "Only orders created at or after this time")]
+        from: Option<String>,
+        #[arg(long, visible_alias = "until", help = "Only orders created before this time")]
+        to: Option<String>,
+        #[arg(long, help = "Filter: pending, paid, shipped, delivered, cancelled, refunded")]
+        status: Option<String>,
+    },
     /// Create an order manually
     #[command(long_about = "Create an order manually (admin use).\n\n\
         For normal checkout flow, use `arky order checkout` instead.\n\n\
@@ -54,27 +89,46 @@ pub enum OrderCommand {
           productId   Product ID (required)\n\
           variantId   Variant ID from the created product's variants array (required)\n\
           quantity    Number of units (required)\n\n\
+        --from-cart builds items from product keys instead (see `order checkout`).\n\n\
         Working example (from integration tests):\n\
         arky order create --data '{\n\
           \"market\": \"us\",\n\
           \"items\": [{\"productId\": \"PRODUCT_ID\", \"variantId\": \"VARIANT_ID\", \"quantity\": 1}],\n\
           \"blocks\": []\n\
-        }'")]
+        }'\n\n\
+        The market is checked against the business configs before sending.\n\
+        Use --no-validate to skip the check.")]
     Create {
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, value_name = "CART", help = "Cart to build items from: inline, @file, or -")]
+        from_cart: Option<String>,
+        #[arg(long, help = "Skip checking the market/currency against business configs")]
+        no_validate: bool,
+        #[arg(long, help = "Print only the created ID, e.g. for ID=$(arky ... --id-only)")]
+        id_only: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Update an order
     #[command(long_about = "Update an order (e.g., change status, add notes).\n\n\
         Optional (--data JSON):\n\
           status   \"pending\" | \"paid\" | \"shipped\" | \"delivered\" | \"cancelled\" | \"refunded\"\n\n\
         Example:\n\
-        arky order update ORDER_ID --data '{\"status\": \"shipped\"}'")]
+        arky order update ORDER_ID --data '{\"status\": \"shipped\"}'\n\n\
+        Partial updates:\n\
+          --patch sends only the given fields via PATCH (if the server supports it).\n\
+          arky order update ORDER_ID --patch --data '{\"status\": \"shipped\"}'")]
     Update {
         /// Order ID
+        #[arg(required_unless_present = "example", default_value = "")]
         id: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Send only the given fields via PATCH instead of a full PUT")]
+        patch: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Get a price quote for items
     #[command(long_about = "Calculate prices for a set of items without creating an order.\n\n\
@@ -92,12 +146,37 @@ pub enum OrderCommand {
           \"items\": [{\"productId\": \"PRODUCT_ID\", \"variantId\": \"VARIANT_ID\", \"quantity\": 2}],\n\
           \"blocks\": []\n\
         }'\n\n\
+        --from-cart builds items from product keys instead (see `order checkout`):\n\
+        arky order quote --from-cart @cart.json --data '{\"market\": \"us\"}'\n\n\
         Response shape:\n\
         {\"subtotal\": 5998, \"discount\": 600, \"tax\": 0, \"total\": 5398,\n\
-         \"currency\": \"USD\", \"items\": [...]}")]
+         \"currency\": \"USD\", \"items\": [...]}\n\n\
+        Quote then checkout without retyping items:\n\
+        arky order quote --save-last --data @cart.json\n\
+        arky order checkout --use-last --data '{\"paymentMethodId\": \"pm_card_visa\"}'")]
     Quote {
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, value_name = "CART", help = "Cart to build items from: inline, @file, or -")]
+        from_cart: Option<String>,
+        #[arg(long, help = "Save this quote to ~/.arky/cache/last-quote.json for `checkout --use-last`")]
+        save_last: bool,
+    },
+    /// Fetch or download the invoice for an order
+    #[command(long_about = "Fetch the invoice/receipt for an order.\n\n\
+        If the server returns a download link, the file (usually PDF) is fetched.\n\
+        PDFs are written to invoice-ORDER_ID.pdf unless --out is given.\n\
+        JSON and HTML invoices are printed to stdout unless --out is given.\n\n\
+        Invoices are generated once an order is paid; unpaid orders have none yet.\n\n\
+        Examples:\n\
+        arky order invoice ORDER_ID\n\
+        arky order invoice ORDER_ID --out invoice.pdf\n\
+        arky order invoice ORDER_ID --out - > invoice.pdf")]
+    Invoice {
+        /// Order ID
+        id: String,
+        #[arg(long, help = "Output file path, or - for stdout")]
+        out: Option<String>,
     },
     /// Checkout: create order and process payment
     #[command(long_about = "Create an order and process payment in one step.\n\n\
@@ -110,6 +189,8 @@ pub enum OrderCommand {
           shippingAddress   {\"name\": \"...\", \"street1\": \"...\", \"city\": \"...\", \"state\": \"...\",\n\
                             \"postalCode\": \"...\", \"country\": \"US\"}\n\
           billingAddress    Same shape as shippingAddress\n\
+                            (name, street1, city, postalCode and a 2-letter\n\
+                            country are checked before sending)\n\
           promoCodeId       Promo code ID for discount\n\
           shippingMethodId  Shipping method ID\n\n\
         Item fields:\n\
@@ -127,14 +208,41 @@ pub enum OrderCommand {
           },\n\
           \"billingAddress\": {\"name\": \"John Doe\", \"street1\": \"123 Main St\",\n\
             \"city\": \"NYC\", \"state\": \"NY\", \"postalCode\": \"10001\", \"country\": \"US\"}\n\
-        }'")]
+        }'\n\n\
+        Items from a cart (--from-cart):\n\
+          A simplified cart replaces items: [{\"sku\": \"t-shirt\", \"variant\": \"small\", \"qty\": 2}]\n\
+          sku is the product key (or ID), variant the variant key (default\n\
+          \"default\"), qty the quantity (default 1). Each product is fetched to\n\
+          fill in productId/variantId; unknown skus or variants fail before sending.\n\
+          arky order checkout --from-cart @cart.json --data '{\"paymentMethodId\": \"pm_card_visa\"}'\n\n\
+        Reusing the last saved quote (see `order quote --save-last`):\n\
+        arky order checkout --use-last --data '{\"paymentMethodId\": \"pm_card_visa\"}'\n\
+        --data fields are merged on top of the saved quote payload.\n\n\
+        Idempotent checkout (--idempotent):\n\
+          Sends Idempotency-Key: sha256 of the final request body (keys sorted),\n\
+          so re-running the exact same checkout doesn't create a second order.\n\
+          Changing any field (even a note or address line) yields a new key,\n\
+          and a deliberate repeat purchase needs a changed body or no flag.")]
     Checkout {
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, value_name = "CART", help = "Cart to build items from: inline, @file, or -")]
+        from_cart: Option<String>,
+        #[arg(long, help = "Start from the payload saved by `order quote --save-last`")]
+        use_last: bool,
+        #[arg(long, help = "Derive an Idempotency-Key from the request body")]
+        idempotent: bool,
     },
 }
 
 pub async fn handle(cmd: OrderCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    if cmd.is_example() {
+        let example = match cmd {
+            OrderCommand::Create { .. } => create_example(),
+            _ => update_example(),
+        };
+        return print_example(&example);
+    }
     let biz_id = client.require_business_id()?;
 
     match cmd {
@@ -142,30 +250,31 @@ pub async fn handle(cmd: OrderCommand, client: &ArkyClient, format: &Format) ->
             let result = client
                 .get(&format!("/v1/businesses/{biz_id}/orders/{id}"), &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         OrderCommand::List {
             status,
             query,
             account_id,
-            limit,
-            cursor,
+            account_email,
+            list,
             sort_field,
             sort_direction,
         } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref s) = status {
                 params.push(("statuses", s.clone()));
             }
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
+            let account_id = match account_email {
+                Some(ref email) => Some(resolve_account_email(client, email).await?),
+                None => account_id,
+            };
             if let Some(ref a) = account_id {
                 params.push(("accountId", a.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             if let Some(ref sf) = sort_field {
                 params.push(("sortField", sf.clone()));
             }
@@ -174,47 +283,435 @@ pub async fn handle(cmd: OrderCommand, client: &ArkyClient, format: &Format) ->
             }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(&format!("/v1/businesses/{biz_id}/orders"), &params_ref)
+            print_list(
+                client,
+                &format!("/v1/businesses/{biz_id}/orders"),
+                &params_ref,
+                &list,
+                format,
+            )
+            .await?;
+        }
+        OrderCommand::Summary { from, to, status } => {
+            let from = from.as_deref().map(parse_time_arg).transpose()?;
+            let to = to.as_deref().map(parse_time_arg).transpose()?;
+            let mut params: Vec<(&str, &str)> = Vec::new();
+            if let Some(ref s) = status {
+                params.push(("statuses", s));
+            }
+            let orders = fetch_all(client, &format!("/v1/businesses/{biz_id}/orders"), &params)
                 .await?;
-            crate::output::print_output(&result, format);
+            let matching: Vec<Value> = orders
+                .into_iter()
+                .filter(|o| {
+                    let created = o["createdAt"].as_i64().unwrap_or(0);
+                    from.is_none_or(|f| created >= f) && to.is_none_or(|t| created < t)
+                })
+                .collect();
+            crate::output::print_output(&summarize_orders(&matching), format)?;
         }
-        OrderCommand::Create { data } => {
-            let body = parse_data(data.as_deref())?;
+        OrderCommand::Create {
+            data,
+            from_cart,
+            no_validate,
+            id_only,
+            ..
+        } => {
+            let mut body = parse_data(data.as_deref())?;
+            if let Some(ref cart) = from_cart {
+                body["items"] = cart_items(client, biz_id, cart).await?;
+            }
+            if !no_validate {
+                let mut configs = BusinessConfigs::new(client);
+                if let Some(market) = body["market"].as_str() {
+                    configs.check_market(market).await?;
+                }
+                if let Some(currency) = body["currency"].as_str() {
+                    configs.check_currency(currency).await?;
+                }
+            }
             let result = client
                 .post(&format!("/v1/businesses/{biz_id}/orders"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            print_created(&result, "order", id_only, format)?;
         }
-        OrderCommand::Update { id, data } => {
-            let mut body = json!({ "id": id });
+        OrderCommand::Update { id, data, patch, .. } => {
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
-            let result = client
-                .put(&format!("/v1/businesses/{biz_id}/orders/{id}"), &body)
-                .await?;
-            crate::output::print_output(&result, format);
+            let path = format!("/v1/businesses/{biz_id}/orders/{id}");
+            let result = if patch {
+                client.patch(&path, &overlay).await?
+            } else {
+                let mut body = json!({ "id": id });
+                merge_data(&mut body, overlay)?;
+                client.put(&path, &body).await?
+            };
+            crate::output::print_output(&result, format)?;
         }
-        OrderCommand::Quote { data } => {
-            let body = parse_data(data.as_deref())?;
+        OrderCommand::Quote {
+            data,
+            from_cart,
+            save_last,
+        } => {
+            let mut body = parse_data(data.as_deref())?;
+            if let Some(ref cart) = from_cart {
+                body["items"] = cart_items(client, biz_id, cart).await?;
+            }
             let result = client
                 .post(&format!("/v1/businesses/{biz_id}/orders/quote"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            if save_last {
+                save_last_quote("last-quote", &body, &result)?;
+            }
+            crate::output::print_output(&result, format)?;
         }
-        OrderCommand::Checkout { data } => {
-            let mut body = parse_data(data.as_deref())?;
+        OrderCommand::Invoice { id, out } => {
+            let path = format!("/v1/businesses/{biz_id}/orders/{id}/invoice");
+            let (mut content_type, mut bytes) = match client.get_bytes(&path).await {
+                Err(CliError::Api {
+                    status,
+                    message,
+                    error,
+                    validation_errors,
+                    request_id,
+                }) if invoice_pending(status, &message, error.as_deref()) => {
+                    return Err(CliError::Api {
+                        status,
+                        message: format!(
+                            "Invoice for order {id} is not available yet (invoices are generated once the order is paid)"
+                        ),
+                        error,
+                        validation_errors,
+                        request_id,
+                    });
+                }
+                other => other?,
+            };
+
+            if content_type.contains("json") {
+                let value: serde_json::Value = serde_json::from_slice(&bytes)?;
+                let link = value
+                    .as_str()
+                    .or_else(|| value.get("url").and_then(|u| u.as_str()))
+                    .filter(|u| u.starts_with("http"));
+                match link {
+                    Some(url) => (content_type, bytes) = client.download(url).await?,
+                    None => {
+                        match out {
+                            Some(o) => {
+                                write_out(&o, serde_json::to_string_pretty(&value)?.as_bytes())?;
+                                if o != "-" {
+                                    crate::output::print_success(&format!("Invoice saved to {o}"));
+                                }
+                            }
+                            None => crate::output::print_output(&value, format)?,
+                        }
+                        return Ok(());
+                    }
+                }
+            }
+
+            let target = out.unwrap_or_else(|| {
+                if content_type.contains("pdf") {
+                    format!("invoice-{id}.pdf")
+                } else {
+                    "-".to_string()
+                }
+            });
+            write_out(&target, &bytes)?;
+            if target != "-" {
+                crate::output::print_success(&format!("Invoice saved to {target}"));
+            }
+        }
+        OrderCommand::Checkout {
+            data,
+            from_cart,
+            use_last,
+            idempotent,
+        } => {
+            let mut body = if use_last {
+                let mut saved = load_last_quote("last-quote", "order quote")?;
+                merge_data(&mut saved, parse_data(data.as_deref())?)?;
+                saved
+            } else {
+                parse_data(data.as_deref())?
+            };
+            if let Some(ref cart) = from_cart {
+                body["items"] = cart_items(client, biz_id, cart).await?;
+            }
             if body.get("businessId").is_none() {
                 body["businessId"] = json!(biz_id);
             }
+            for label in ["shippingAddress", "billingAddress"] {
+                if let Some(address) = body.get(label).filter(|a| !a.is_null()) {
+                    validate_address(address, label)?;
+                }
+            }
+            let client = if idempotent {
+                let key = HeaderValue::from_str(&idempotency_key(&body))
+                    .expect("hex digest is a valid header value");
+                client.clone().with_header(IDEMPOTENCY_KEY, key)
+            } else {
+                client.clone()
+            };
             let result = client
                 .post(
                     &format!("/v1/businesses/{biz_id}/orders/checkout"),
                     &body,
                 )
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
     }
     Ok(())
 }
+
+/// `--example` skeleton for create
+fn create_example() -> Value {
+    json!({
+        "market": "us",
+        "items": [{ "productId": "PRODUCT_ID", "variantId": "VARIANT_ID", "quantity": 1 }],
+        "blocks": []
+    })
+}
+
+/// `--example` skeleton for update
+fn update_example() -> Value {
+    json!({ "status": "shipped" })
+}
+
+/// Turn a `--from-cart` list of `{"sku", "variant", "qty"}` lines into order
+/// items, fetching each product once. Every unresolved line is reported.
+async fn cart_items(client: &ArkyClient, biz_id: &str, cart: &str) -> Result<Value> {
+    let cart = parse_data(Some(cart))?;
+    let lines = cart
+        .as_array()
+        .filter(|lines| !lines.is_empty())
+        .ok_or_else(|| {
+            CliError::InvalidInput(
+                "--from-cart must be a non-empty array of {\"sku\", \"variant\", \"qty\"}".into(),
+            )
+        })?;
+
+    let mut products: HashMap<&str, Option<Value>> = HashMap::new();
+    let mut items = Vec::new();
+    let mut unresolved = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let Some(sku) = line["sku"].as_str().filter(|s| !s.is_empty()) else {
+            unresolved.push(format!("line {} has no sku", i + 1));
+            continue;
+        };
+        if !products.contains_key(sku) {
+            let product = match client
+                .get(&format!("/v1/businesses/{biz_id}/products/{sku}"), &[])
+                .await
+            {
+                Err(CliError::Api { status: 404, .. }) => None,
+                other => Some(other?),
+            };
+            products.insert(sku, product);
+        }
+        match products[sku].as_ref() {
+            Some(product) => match cart_item(line, product) {
+                Ok(item) => items.push(item),
+                Err(reason) => unresolved.push(format!("{sku}: {reason}")),
+            },
+            None => unresolved.push(format!("{sku}: no such product")),
+        }
+    }
+    if !unresolved.is_empty() {
+        return Err(CliError::InvalidInput(format!(
+            "Could not resolve cart: {}",
+            unresolved.join("; ")
+        )));
+    }
+    Ok(Value::Array(items))
+}
+
+/// One order item for a cart line, with the variant picked by key from the
+/// product (`variant` defaults to "default", `qty` to 1)
+fn cart_item(line: &Value, product: &Value) -> std::result::Result<Value, String> {
+    let product = product.get("data").filter(|d| d.is_object()).unwrap_or(product);
+    let product_id = product["id"].as_str().ok_or("product has no id")?;
+    let variant_key = line["variant"].as_str().unwrap_or("default");
+    let quantity = match &line["qty"] {
+        Value::Null => 1,
+        qty => qty.as_u64().filter(|q| *q > 0).ok_or("qty must be a positive integer")?,
+    };
+    let variants = product["variants"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let variant_id = variants
+        .iter()
+        .find(|v| v["key"] == variant_key)
+        .and_then(|v| v["id"].as_str())
+        .ok_or_else(|| {
+            let keys: Vec<&str> = variants.iter().filter_map(|v| v["key"].as_str()).collect();
+            format!("no variant '{variant_key}' (has: {})", keys.join(", "))
+        })?;
+    Ok(json!({ "productId": product_id, "variantId": variant_id, "quantity": quantity }))
+}
+
+/// Whether an invoice error means "not generated yet" rather than a bad order:
+/// a 409, or a 404 that names the invoice (a missing order is a plain 404).
+fn invoice_pending(status: u16, message: &str, error: Option<&str>) -> bool {
+    let names_invoice = |s: &str| s.to_lowercase().contains("invoice");
+    status == 409 || (status == 404 && (names_invoice(message) || error.is_some_and(names_invoice)))
+}
+
+/// sha256 (hex) of the compact body with object keys sorted at every level,
+/// so the same payload always hashes the same regardless of key order.
+fn idempotency_key(body: &Value) -> String {
+    let mut canonical = String::new();
+    write_sorted(body, &mut canonical);
+    Sha256::digest(canonical.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Compact JSON with object keys sorted, independent of how the map orders them
+fn write_sorted(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::from(key.as_str()).to_string());
+                out.push(':');
+                write_sorted(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_sorted(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+/// Count orders and sum their totals per currency, overall and per status
+fn summarize_orders(orders: &[Value]) -> Value {
+    fn add(entry: &mut Value, currency: &str, amount: i64) {
+        entry["count"] = json!(entry["count"].as_u64().unwrap_or(0) + 1);
+        if !entry["totals"].is_object() {
+            entry["totals"] = json!({});
+        }
+        let total = &mut entry["totals"][currency];
+        *total = json!(total.as_i64().unwrap_or(0) + amount);
+    }
+
+    let mut overall = json!({ "count": 0, "totals": {} });
+    let mut by_status = Map::new();
+    for order in orders {
+        let payment = &order["payment"];
+        let amount = order["total"]
+            .as_i64()
+            .or_else(|| payment["total"].as_i64())
+            .unwrap_or(0);
+        let currency = order["currency"]
+            .as_str()
+            .or_else(|| payment["currency"].as_str())
+            .unwrap_or("unknown")
+            .to_lowercase();
+        let status = order["status"].as_str().unwrap_or("unknown").to_string();
+
+        add(&mut overall, &currency, amount);
+        add(by_status.entry(status).or_insert(json!({ "count": 0 })), &currency, amount);
+    }
+    overall["byStatus"] = Value::Object(by_status);
+    overall
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invoice_pending() {
+        assert!(invoice_pending(409, "Conflict", None));
+        assert!(invoice_pending(404, "Invoice not found", None));
+        assert!(invoice_pending(404, "Not found", Some("INVOICE_NOT_FOUND")));
+        assert!(!invoice_pending(404, "Order not found", None));
+        assert!(!invoice_pending(500, "Invoice failed", None));
+    }
+
+    #[test]
+    fn test_idempotency_key_ignores_key_order() {
+        let a: Value =
+            serde_json::from_str(r#"{"items": [{"productId": "p1", "quantity": 1}], "market": "us"}"#)
+                .unwrap();
+        let b: Value =
+            serde_json::from_str(r#"{"market": "us", "items": [{"quantity": 1, "productId": "p1"}]}"#)
+                .unwrap();
+        assert_eq!(idempotency_key(&a), idempotency_key(&b));
+        assert_eq!(idempotency_key(&a).len(), 64);
+
+        let mut c = a.clone();
+        c["items"][0]["quantity"] = json!(2);
+        assert_ne!(idempotency_key(&a), idempotency_key(&c));
+    }
+
+    #[test]
+    fn test_idempotency_key_sorts_inserted_keys() {
+        let mut item = serde_json::Map::new();
+        item.insert("quantity".into(), json!(1));
+        item.insert("productId".into(), json!("p1"));
+        let mut body = serde_json::Map::new();
+        body.insert("market".into(), json!("us"));
+        body.insert("items".into(), Value::Array(vec![Value::Object(item)]));
+
+        let mut canonical = String::new();
+        write_sorted(&Value::Object(body), &mut canonical);
+        assert_eq!(canonical, r#"{"items":[{"productId":"p1","quantity":1}],"market":"us"}"#);
+    }
+
+    #[test]
+    fn test_summarize_orders() {
+        let orders = vec![
+            json!({"status": "paid", "total": 1000, "currency": "USD"}),
+            json!({"status": "paid", "payment": {"total": 500, "currency": "usd"}}),
+            json!({"status": "pending", "total": 700, "currency": "eur"}),
+        ];
+        let summary = summarize_orders(&orders);
+        assert_eq!(summary["count"], 3);
+        assert_eq!(summary["totals"]["usd"], 1500);
+        assert_eq!(summary["totals"]["eur"], 700);
+        assert_eq!(summary["byStatus"]["paid"]["count"], 2);
+        assert_eq!(summary["byStatus"]["pending"]["totals"]["eur"], 700);
+    }
+
+    #[test]
+    fn test_cart_item() {
+        let product = json!({"id": "p1", "key": "t-shirt", "variants": [
+            {"id": "v1", "key": "default"},
+            {"id": "v2", "key": "small"}
+        ]});
+        assert_eq!(
+            cart_item(&json!({"sku": "t-shirt", "variant": "small", "qty": 2}), &product),
+            Ok(json!({"productId": "p1", "variantId": "v2", "quantity": 2}))
+        );
+        assert_eq!(
+            cart_item(&json!({"sku": "t-shirt"}), &json!({ "data": product.clone() })),
+            Ok(json!({"productId": "p1", "variantId": "v1", "quantity": 1}))
+        );
+        let missing = cart_item(&json!({"sku": "t-shirt", "variant": "xl"}), &product);
+        assert_eq!(missing, Err("no variant 'xl' (has: default, small)".to_string()));
+        assert!(cart_item(&json!({"sku": "t-shirt", "qty": 0}), &product).is_err());
+    }
+
+    #[test]
+    fn test_summarize_no_orders() {
+        let summary = summarize_orders(&[]);
+        assert_eq!(summary["count"], 0);
+        assert_eq!(summary["totals"], json!({}));
+    }
+}
@@ -1,29 +1,55 @@
 use crate::client::ArkyClient;
-use crate::error::Result;
+use crate::commands::{grep_items, page_items};
+use crate::error::{CliError, Result};
 use crate::output::Format;
 use clap::Subcommand;
+use serde_json::Value;
+
+/// Most near-miss country codes listed in a "did you mean" hint
+const MAX_COUNTRY_SUGGESTIONS: usize = 5;
 
 #[derive(Subcommand, Debug)]
 pub enum PlatformCommand {
     /// List supported currencies
     #[command(long_about = "List all currencies supported by the platform.\n\n\
+        Cached locally in ~/.arky/cache for 24h. Use --refresh to re-fetch.\n\n\
         Example:\n\
-        arky platform currencies")]
-    Currencies,
+        arky platform currencies\n\
+        arky platform currencies --refresh\n\
+        arky platform currencies --grep eur")]
+    Currencies {
+        #[arg(long, help = "Bypass the local cache and fetch fresh data")]
+        refresh: bool,
+        #[arg(long, value_name = "PATTERN", help = "Keep entries matching a regex or substring")]
+        grep: Option<String>,
+    },
     /// List available integration services
     #[command(long_about = "List all integration services available on the platform.\n\n\
         Example:\n\
-        arky platform integrations")]
-    Integrations,
+        arky platform integrations\n\
+        arky platform integrations --grep stripe")]
+    Integrations {
+        #[arg(long, value_name = "PATTERN", help = "Keep entries matching a regex or substring")]
+        grep: Option<String>,
+    },
     /// List countries and their states/regions
     #[command(long_about = "List all countries with their states/regions.\n\n\
+        Cached locally in ~/.arky/cache for 24h. Use --refresh to re-fetch.\n\n\
         Example:\n\
-        arky platform countries\n\n\
+        arky platform countries\n\
+        arky platform countries --refresh\n\n\
         Response shape:\n\
         {\"items\": [{\"code\": \"US\", \"name\": \"United States\", \"states\": [{\"code\": \"NY\", \"name\": \"New York\"}]}]}")]
-    Countries,
+    Countries {
+        #[arg(long, help = "Bypass the local cache and fetch fresh data")]
+        refresh: bool,
+        #[arg(long, value_name = "PATTERN", help = "Keep entries matching a regex or substring")]
+        grep: Option<String>,
+    },
     /// Get a specific country with states
     #[command(long_about = "Fetch a specific country by ISO code.\n\n\
+        The code is case-insensitive (us = US). An unknown code suggests the\n\
+        closest matches from the (cached) countries list.\n\n\
         Example:\n\
         arky platform country US\n\n\
         Response shape:\n\
@@ -35,37 +61,131 @@ pub enum PlatformCommand {
     /// List available webhook event types
     #[command(name = "webhook-events", long_about = "List all event types available for webhooks.\n\n\
         Example:\n\
-        arky platform webhook-events\n\n\
+        arky platform webhook-events\n\
+        arky platform webhook-events --grep '^order\\.'\n\n\
         Response: array of event type strings (e.g. \"order.paid\", \"booking.confirmed\")")]
-    WebhookEvents,
+    WebhookEvents {
+        #[arg(long, value_name = "PATTERN", help = "Keep entries matching a regex or substring")]
+        grep: Option<String>,
+    },
 }
 
 pub async fn handle(cmd: PlatformCommand, client: &ArkyClient, format: &Format) -> Result<()> {
     match cmd {
-        PlatformCommand::Currencies => {
-            let result = client.get("/v1/platform/currencies", &[]).await?;
-            crate::output::print_output(&result, format);
+        PlatformCommand::Currencies { refresh, grep } => {
+            let mut result = client
+                .get_cached("/v1/platform/currencies", refresh)
+                .await?;
+            if let Some(ref pattern) = grep {
+                grep_items(&mut result, pattern);
+            }
+            crate::output::print_output(&result, format)?;
         }
-        PlatformCommand::Integrations => {
-            let result = client
+        PlatformCommand::Integrations { grep } => {
+            let mut result = client
                 .get("/v1/platform/integration-services", &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            if let Some(ref pattern) = grep {
+                grep_items(&mut result, pattern);
+            }
+            crate::output::print_output(&result, format)?;
         }
-        PlatformCommand::Countries => {
-            let result = client.get("/v1/platform/countries", &[]).await?;
-            crate::output::print_output(&result, format);
+        PlatformCommand::Countries { refresh, grep } => {
+            let mut result = client
+                .get_cached("/v1/platform/countries", refresh)
+                .await?;
+            if let Some(ref pattern) = grep {
+                grep_items(&mut result, pattern);
+            }
+            crate::output::print_output(&result, format)?;
         }
         PlatformCommand::Country { code } => {
-            let result = client
+            let code = normalize_country_code(&code)?;
+            let result = match client
                 .get(&format!("/v1/platform/countries/{code}"), &[])
-                .await?;
-            crate::output::print_output(&result, format);
+                .await
+            {
+                Err(e @ CliError::Api { status: 404, .. }) => {
+                    let countries = client.get_cached("/v1/platform/countries", false).await?;
+                    let suggestions = suggest_countries(&countries, &code);
+                    if suggestions.is_empty() {
+                        return Err(e);
+                    }
+                    return Err(CliError::InvalidInput(format!(
+                        "Unknown country code {code}. Did you mean {}?",
+                        suggestions.join(", ")
+                    )));
+                }
+                other => other?,
+            };
+            crate::output::print_output(&result, format)?;
         }
-        PlatformCommand::WebhookEvents => {
-            let result = client.get("/v1/platform/events", &[]).await?;
-            crate::output::print_output(&result, format);
+        PlatformCommand::WebhookEvents { grep } => {
+            let mut result = client.get("/v1/platform/events", &[]).await?;
+            if let Some(ref pattern) = grep {
+                grep_items(&mut result, pattern);
+            }
+            crate::output::print_output(&result, format)?;
         }
     }
     Ok(())
 }
+
+/// Uppercase an ISO 3166-1 alpha-2 code, rejecting anything but two letters
+fn normalize_country_code(code: &str) -> Result<String> {
+    let code = code.trim();
+    if code.len() != 2 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(CliError::InvalidInput(format!(
+            "Invalid country code '{code}'. Use a 2-letter ISO code, e.g. US, GB, DE"
+        )));
+    }
+    Ok(code.to_ascii_uppercase())
+}
+
+/// Known codes one letter away from `code`, as "US (United States)"
+fn suggest_countries(countries: &Value, code: &str) -> Vec<String> {
+    let Some(items) = page_items(countries) else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|c| Some((c["code"].as_str()?, c["name"].as_str().unwrap_or_default())))
+        .filter(|(known, _)| {
+            known.len() == code.len()
+                && known.chars().zip(code.chars()).filter(|(a, b)| a != b).count() == 1
+        })
+        .take(MAX_COUNTRY_SUGGESTIONS)
+        .map(|(known, name)| {
+            if name.is_empty() {
+                known.to_string()
+            } else {
+                format!("{known} ({name})")
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize_country_code() {
+        assert_eq!(normalize_country_code("us").unwrap(), "US");
+        assert_eq!(normalize_country_code(" Gb ").unwrap(), "GB");
+        assert!(normalize_country_code("USA").is_err());
+        assert!(normalize_country_code("U1").is_err());
+    }
+
+    #[test]
+    fn test_suggest_countries() {
+        let countries = json!({"items": [
+            {"code": "US", "name": "United States"},
+            {"code": "GB", "name": "United Kingdom"},
+            {"code": "DE", "name": "Germany"}
+        ]});
+        assert_eq!(suggest_countries(&countries, "UZ"), vec!["US (United States)"]);
+        assert!(suggest_countries(&countries, "ZZ").is_empty());
+    }
+}
@@ -1,9 +1,20 @@
 use crate::client::ArkyClient;
-use crate::commands::{merge_data, parse_data};
+use crate::commands::business::BusinessConfigs;
+use crate::commands::{
+    check_known_fields, confirm_delete, create_or_update, delete_entity, delete_from_stdin,
+    find_entry, keyed_records, merge_data, note_failed_record, parse_data, parse_records,
+    preserve_required_fields, print_created, print_example, print_list, set_price, DeleteArgs,
+    Example, ListArgs, PRODUCT_REQUIRED_FIELDS,
+};
 use crate::error::Result;
 use crate::output::Format;
 use clap::Subcommand;
-use serde_json::json;
+use serde_json::{json, Value};
+
+/// Top-level product fields accepted in --data (checked with --strict)
+const PRODUCT_FIELDS: &[&str] = &[
+    "key", "slug", "status", "audienceIds", "networkIds", "filters", "blocks", "variants",
+];
 
 #[derive(Subcommand, Debug)]
 pub enum ProductCommand {
@@ -33,10 +44,8 @@ pub enum ProductCommand {
     List {
         #[arg(long)]
         query: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
         #[arg(long, help = "Filter: draft, active, archived")]
         status: Option<String>,
         #[arg(long)]
@@ -78,12 +87,26 @@ pub enum ProductCommand {
         \"inventory\": [{\"locationId\": \"default\", \"available\": 100, \"reserved\": 0}],\n\
         \"attributes\": []\n\
       }]\n\
-    }'")]
+    }'\n\n\
+    Each price's market and currency are checked against the business configs\n\
+    before sending. Use --no-validate to skip the check.\n\n\
+    Batch create (NDJSON):\n\
+      Several newline-delimited JSON objects in --data create one product each,\n\
+      in order. Omit KEY and give each record its own \"key\".\n\
+      cat products.ndjson | arky product create --data -")]
     Create {
-        /// Product key (unique within business, URL-safe)
-        key: String,
+        /// Product key (unique within business, URL-safe); omit for multi-record --data
+        key: Option<String>,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Skip checking markets/currencies against business configs")]
+        no_validate: bool,
+        #[arg(long, help = "Print only the created ID, e.g. for ID=$(arky ... --id-only)")]
+        id_only: bool,
+        #[arg(long, help = "If KEY is taken, update that product instead of failing")]
+        upsert: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Update a product
     #[command(long_about = "Update a product by ID.\n\n\
@@ -94,21 +117,73 @@ pub enum ProductCommand {
           status     \"draft\" | \"active\" | \"archived\"\n\n\
         Example:\n\
         arky product update PROD_ID --data '{\"blocks\": [...], \"variants\": [...]}'\n\
-        arky product update PROD_ID --data '{\"status\": \"active\"}'")]
+        arky product update PROD_ID --data '{\"status\": \"active\"}'\n\n\
+        Preserved fields:\n\
+          key, slug, status, audienceIds, networkIds, filters, blocks and variants\n\
+          are copied from the current product when missing from --data.\n\
+          Use --force to send the payload as-is.\n\n\
+        Partial updates:\n\
+          --patch sends only the given fields via PATCH (if the server supports it).\n\
+          arky product update PROD_ID --patch --data '{\"status\": \"active\"}'")]
     Update {
         /// Product ID
+        #[arg(required_unless_present = "example", default_value = "")]
         id: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Send only the given fields via PATCH instead of a full PUT")]
+        patch: bool,
+        #[arg(long, help = "Don't fill in required fields missing from --data")]
+        force: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
+    },
+    /// Set one variant's price for a market and currency
+    #[command(long_about = "Set the price of one product variant without resending the\n\
+        whole variants array. The product is fetched, the variant's price for\n\
+        --market/--currency is updated (or added if missing), and the product\n\
+        is saved back.\n\n\
+        --amount is in minor units (cents). The market and currency are checked\n\
+        against the business configs; use --no-validate to skip the check.\n\n\
+        Example:\n\
+        arky product set-price PROD_ID --variant default --market us --currency usd --amount 2499")]
+    SetPrice {
+        /// Product ID
+        id: String,
+        #[arg(long, default_value = "default", help = "Variant key")]
+        variant: String,
+        #[arg(long)]
+        market: String,
+        #[arg(long)]
+        currency: String,
+        #[arg(long, help = "Price in minor units (cents)")]
+        amount: u64,
+        #[arg(long, help = "Skip checking the market/currency against business configs")]
+        no_validate: bool,
     },
     /// Delete a product
+    #[command(long_about = "Delete a product by ID.\n\n\
+        --show fetches it first, prints its id/key/status/title and asks before\n\
+        deleting; add --yes to skip the question.\n\n\
+        --stdin deletes every ID piped in (one per line, or a JSON array of IDs or\n\
+        objects with an id), reporting each and continuing past failures unless\n\
+        --stop-on-error is given.\n\n\
+        Examples:\n\
+        arky product delete PRODUCT_ID --show\n\
+        arky product list --all --pointer /data | arky product delete --stdin")]
     Delete {
         /// Product ID
+        #[arg(required_unless_present = "stdin", default_value = "")]
         id: String,
+        #[command(flatten)]
+        delete: DeleteArgs,
     },
 }
 
 pub async fn handle(cmd: ProductCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    if cmd.is_example() {
+        return print_example(&example());
+    }
     let biz_id = client.require_business_id()?;
 
     match cmd {
@@ -116,23 +191,19 @@ pub async fn handle(cmd: ProductCommand, client: &ArkyClient, format: &Format) -
             let result = client
                 .get(&format!("/v1/businesses/{biz_id}/products/{id}"), &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         ProductCommand::List {
             query,
-            limit,
-            cursor,
+            list,
             status,
             sort_field,
             sort_direction,
         } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             if let Some(ref s) = status {
                 params.push(("status", s.clone()));
             }
@@ -144,35 +215,124 @@ pub async fn handle(cmd: ProductCommand, client: &ArkyClient, format: &Format) -
             }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(&format!("/v1/businesses/{biz_id}/products"), &params_ref)
-                .await?;
-            crate::output::print_output(&result, format);
+            print_list(
+                client,
+                &format!("/v1/businesses/{biz_id}/products"),
+                &params_ref,
+                &list,
+                format,
+            )
+            .await?;
         }
-        ProductCommand::Create { key, data } => {
-            let mut body = json!({ "key": key });
-            let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
-            let result = client
-                .post(&format!("/v1/businesses/{biz_id}/products"), &body)
-                .await?;
-            crate::output::print_output(&result, format);
+        ProductCommand::Create {
+            key,
+            data,
+            no_validate,
+            id_only,
+            upsert,
+            ..
+        } => {
+            let bodies = keyed_records(key, parse_records(data.as_deref())?)?;
+            for body in &bodies {
+                check_known_fields(body, PRODUCT_FIELDS, "product")?;
+            }
+            if !no_validate {
+                let mut configs = BusinessConfigs::new(client);
+                for body in &bodies {
+                    for variant in body["variants"].as_array().into_iter().flatten() {
+                        configs.check_prices(&variant["prices"]).await?;
+                    }
+                }
+            }
+            let path = format!("/v1/businesses/{biz_id}/products");
+            let total = bodies.len();
+            for (i, body) in bodies.iter().enumerate() {
+                let result = create_or_update(client, &path, "product", body, upsert)
+                    .await
+                    .inspect_err(|_| note_failed_record(i, total))?;
+                print_created(&result, "product", id_only, format)?;
+            }
         }
-        ProductCommand::Update { id, data } => {
-            let mut body = json!({ "id": id });
+        ProductCommand::Update {
+            id,
+            data,
+            patch,
+            force,
+            ..
+        } => {
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
-            let result = client
-                .put(&format!("/v1/businesses/{biz_id}/products/{id}"), &body)
-                .await?;
-            crate::output::print_output(&result, format);
+            check_known_fields(&overlay, PRODUCT_FIELDS, "product")?;
+            let path = format!("/v1/businesses/{biz_id}/products/{id}");
+            let result = if patch {
+                client.patch(&path, &overlay).await?
+            } else {
+                let mut body = json!({ "id": id });
+                merge_data(&mut body, overlay)?;
+                if !force {
+                    preserve_required_fields(client, &path, &mut body, PRODUCT_REQUIRED_FIELDS).await?;
+                }
+                client.put(&path, &body).await?
+            };
+            crate::output::print_output(&result, format)?;
         }
-        ProductCommand::Delete { id } => {
-            let _ = client
-                .delete(&format!("/v1/businesses/{biz_id}/products/{id}"))
-                .await?;
-            crate::output::print_success("Product deleted");
+        ProductCommand::SetPrice {
+            id,
+            variant,
+            market,
+            currency,
+            amount,
+            no_validate,
+        } => {
+            if !no_validate {
+                let mut configs = BusinessConfigs::new(client);
+                configs.check_market(&market).await?;
+                configs.check_currency(&currency).await?;
+            }
+            let path = format!("/v1/businesses/{biz_id}/products/{id}");
+            let mut product = client.get(&path, &[]).await?;
+            let entry = find_entry(&mut product, "variants", "key", &variant)?;
+            set_price(&mut entry["prices"], &market, &currency, amount);
+            let result = client.put(&path, &product).await?;
+            crate::output::print_output(&result, format)?;
+        }
+        ProductCommand::Delete { id, delete } => {
+            let collection = format!("/v1/businesses/{biz_id}/products");
+            if delete.stdin {
+                return delete_from_stdin(client, &collection, "product", &delete).await;
+            }
+            let path = format!("{collection}/{id}");
+            if !confirm_delete(client, &path, "product", &delete, format).await? {
+                return Ok(());
+            }
+            if delete_entity(client, &path, "product", delete.ignore_missing).await? {
+                crate::output::print_success("Product deleted");
+            }
         }
     }
     Ok(())
 }
+
+fn example() -> Value {
+    json!({
+        "slug": { "en": "t-shirt" },
+        "status": "active",
+        "audienceIds": [],
+        "networkIds": [],
+        "filters": [],
+        "blocks": [
+            {
+                "type": "localized_text",
+                "id": "b1",
+                "key": "title",
+                "properties": {},
+                "value": { "en": "T-Shirt" }
+            }
+        ],
+        "variants": [{
+            "key": "default",
+            "prices": [{ "currency": "usd", "market": "us", "amount": 1999 }],
+            "inventory": [{ "locationId": "default", "available": 100, "reserved": 0 }],
+            "attributes": []
+        }]
+    })
+}
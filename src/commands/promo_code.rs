@@ -1,9 +1,12 @@
 use crate::client::ArkyClient;
-use crate::commands::{merge_data, parse_data};
+use crate::commands::{
+    delete_entity, merge_data, parse_data, print_created, print_example, print_list, set_status,
+    Example, ListArgs,
+};
 use crate::error::Result;
 use crate::output::Format;
 use clap::Subcommand;
-use serde_json::json;
+use serde_json::{json, Value};
 
 #[derive(Subcommand, Debug)]
 pub enum PromoCodeCommand {
@@ -28,10 +31,8 @@ pub enum PromoCodeCommand {
     List {
         #[arg(long)]
         query: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
         #[arg(long, help = "Comma-separated: active,expired,disabled")]
         statuses: Option<String>,
     },
@@ -71,6 +72,10 @@ pub enum PromoCodeCommand {
     Create {
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Print only the created ID, e.g. for ID=$(arky ... --id-only)")]
+        id_only: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Update a promo code
     #[command(long_about = "Update a promo code by ID.\n\n\
@@ -78,18 +83,43 @@ pub enum PromoCodeCommand {
         arky promo-code update PROMO_ID --data '{\"conditions\": [{\"type\": \"max_uses\", \"value\": 200}]}'")]
     Update {
         /// Promo code ID
+        #[arg(required_unless_present = "example", default_value = "")]
         id: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
+    },
+    /// Turn a promo code off (status "disabled")
+    #[command(long_about = "Disable a promo code without touching its discounts or conditions.\n\n\
+        Fetches the code, sets status to \"disabled\", and PUTs the full object back.\n\n\
+        Example:\n\
+        arky promo-code disable PROMO_ID")]
+    Disable {
+        /// Promo code ID
+        id: String,
+    },
+    /// Turn a promo code back on (status "active")
+    #[command(long_about = "Re-enable a disabled promo code, keeping discounts and conditions.\n\n\
+        Example:\n\
+        arky promo-code enable PROMO_ID")]
+    Enable {
+        /// Promo code ID
+        id: String,
     },
     /// Delete a promo code
     Delete {
         /// Promo code ID
         id: String,
+        #[arg(long, help = "Treat an already-deleted ID (404) as success")]
+        ignore_missing: bool,
     },
 }
 
 pub async fn handle(cmd: PromoCodeCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    if cmd.is_example() {
+        return print_example(&example());
+    }
     let biz_id = client.require_business_id()?;
 
     match cmd {
@@ -97,55 +127,73 @@ pub async fn handle(cmd: PromoCodeCommand, client: &ArkyClient, format: &Format)
             let result = client
                 .get(&format!("/v1/businesses/{biz_id}/promo-codes/{id}"), &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         PromoCodeCommand::List {
             query,
-            limit,
-            cursor,
+            list,
             statuses,
         } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             if let Some(ref s) = statuses {
                 params.push(("statuses", s.clone()));
             }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(&format!("/v1/businesses/{biz_id}/promo-codes"), &params_ref)
-                .await?;
-            crate::output::print_output(&result, format);
+            print_list(
+                client,
+                &format!("/v1/businesses/{biz_id}/promo-codes"),
+                &params_ref,
+                &list,
+                format,
+            )
+            .await?;
         }
-        PromoCodeCommand::Create { data } => {
+        PromoCodeCommand::Create { data, id_only, .. } => {
             let mut body = json!({ "businessId": biz_id });
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
+            merge_data(&mut body, overlay)?;
             let result = client
                 .post(&format!("/v1/businesses/{biz_id}/promo-codes"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            print_created(&result, "promo-code", id_only, format)?;
         }
-        PromoCodeCommand::Update { id, data } => {
+        PromoCodeCommand::Update { id, data, .. } => {
             let mut body = json!({ "id": id });
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
+            merge_data(&mut body, overlay)?;
             let result = client
                 .put(&format!("/v1/businesses/{biz_id}/promo-codes/{id}"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
-        PromoCodeCommand::Delete { id } => {
-            let _ = client
-                .delete(&format!("/v1/businesses/{biz_id}/promo-codes/{id}"))
-                .await?;
-            crate::output::print_success("Promo code deleted");
+        PromoCodeCommand::Disable { id } => {
+            let path = format!("/v1/businesses/{biz_id}/promo-codes/{id}");
+            let result = set_status(client, &path, "disabled").await?;
+            crate::output::print_output(&result, format)?;
+        }
+        PromoCodeCommand::Enable { id } => {
+            let path = format!("/v1/businesses/{biz_id}/promo-codes/{id}");
+            let result = set_status(client, &path, "active").await?;
+            crate::output::print_output(&result, format)?;
+        }
+        PromoCodeCommand::Delete { id, ignore_missing } => {
+            let path = format!("/v1/businesses/{biz_id}/promo-codes/{id}");
+            if delete_entity(client, &path, "promo code", ignore_missing).await? {
+                crate::output::print_success("Promo code deleted");
+            }
         }
     }
     Ok(())
 }
+
+fn example() -> Value {
+    json!({
+        "code": "SUMMER20",
+        "discounts": [{ "type": "items_percentage", "marketId": "us", "bps": 1500 }],
+        "conditions": [{ "type": "max_uses", "value": { "type": "count", "value": 50 } }]
+    })
+}
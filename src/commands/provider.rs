@@ -1,9 +1,23 @@
 use crate::client::ArkyClient;
-use crate::commands::{merge_data, parse_data};
-use crate::error::Result;
+use crate::commands::service::{validate_working_time, WEEKDAYS};
+use crate::commands::{
+    check_known_fields, confirm_delete, create_or_update, delete_entity, delete_from_stdin,
+    fetch_all, fetch_list, keyed_records, merge_data, note_failed_record, parse_data,
+    parse_records, parse_time_arg, preserve_required_fields, print_created, print_example,
+    DeleteArgs, Example, ListArgs, PROVIDER_REQUIRED_FIELDS,
+};
+use crate::error::{CliError, Result};
 use crate::output::Format;
 use clap::Subcommand;
-use serde_json::json;
+use serde_json::{json, Value};
+
+/// Booking statuses that no longer hold a provider's time
+const RELEASED_BOOKING_STATUSES: &[&str] = &["cancelled", "canceled"];
+
+/// Top-level provider fields accepted in --data (checked with --strict)
+const PROVIDER_FIELDS: &[&str] = &[
+    "key", "slug", "status", "audienceIds", "networkIds", "filters", "blocks", "concurrentLimit",
+];
 
 #[derive(Subcommand, Debug)]
 pub enum ProviderCommand {
@@ -22,19 +36,39 @@ pub enum ProviderCommand {
     /// List providers
     #[command(long_about = "List service providers.\n\n\
         Optionally filter by service to see which providers offer a specific service.\n\n\
+        --available-at keeps only providers free at that time for --service-id:\n\
+        the instant falls in their weekly workingHours for the service, and they\n\
+        have fewer overlapping bookings than their concurrentLimit (default 1).\n\
+        Working hours are read as UTC shifted by --utc-offset minutes (e.g. 60\n\
+        for UTC+1); outcastDates and specificDates are not considered.\n\
+        Every page is fetched before filtering, so --limit and --count apply to\n\
+        the free providers.\n\n\
         Examples:\n\
         arky provider list\n\
         arky provider list --service-id SVC_ID\n\
-        arky provider list --statuses active")]
+        arky provider list --statuses active\n\
+        arky provider list --service-id SVC_ID --available-at 1735725600 --utc-offset 60")]
     List {
         #[arg(long)]
         query: Option<String>,
         #[arg(long, help = "Filter providers by service ID")]
         service_id: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[arg(
+            long,
+            requires = "service_id",
+            help = "Only providers free at this time: epoch, YYYY-MM-DD, now, +2h"
+        )]
+        available_at: Option<String>,
+        #[arg(
+            long,
+            default_value_t = 0,
+            allow_hyphen_values = true,
+            value_name = "MINUTES",
+            help = "Business time zone offset from UTC for --available-at"
+        )]
+        utc_offset: i64,
+        #[command(flatten)]
+        list: ListArgs,
         #[arg(long, help = "Comma-separated: draft,active,archived")]
         statuses: Option<String>,
     },
@@ -66,12 +100,22 @@ pub enum ProviderCommand {
         {\"type\": \"markdown\", \"id\": \"b2\", \"key\": \"bio\", \"properties\": {}, \"value\": {\"en\": \"Integration test provider\"}}\n\
       ],\n\
       \"concurrentLimit\": 1\n\
-    }'")]
+    }'\n\n\
+    Batch create (NDJSON):\n\
+      Several newline-delimited JSON objects in --data create one provider each,\n\
+      in order. Omit KEY and give each record its own \"key\".\n\
+      cat providers.ndjson | arky provider create --data -")]
     Create {
-        /// Provider key (unique within business, URL-safe)
-        key: String,
+        /// Provider key (unique within business, URL-safe); omit for multi-record --data
+        key: Option<String>,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Print only the created ID, e.g. for ID=$(arky ... --id-only)")]
+        id_only: bool,
+        #[arg(long, help = "If KEY is taken, update that provider instead of failing")]
+        upsert: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Update a provider
     #[command(long_about = "Update a provider by ID.\n\n\
@@ -81,17 +125,38 @@ pub enum ProviderCommand {
           status           \"draft\" | \"active\" | \"archived\"\n\n\
         Example:\n\
         arky provider update PROV_ID --data '{\"blocks\": [...], \"concurrentLimit\": 2}'\n\
-        arky provider update PROV_ID --data '{\"status\": \"active\"}'")]
+        arky provider update PROV_ID --data '{\"status\": \"active\"}'\n\n\
+        Preserved fields:\n\
+          key, slug, status, audienceIds, networkIds, filters, blocks and concurrentLimit\n\
+          are copied from the current provider when missing from --data.\n\
+          Use --force to send the payload as-is.")]
     Update {
         /// Provider ID
+        #[arg(required_unless_present = "example", default_value = "")]
         id: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Don't fill in required fields missing from --data")]
+        force: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Delete a provider
+    #[command(long_about = "Delete a provider by ID.\n\n\
+        --show fetches it first, prints its id/key/status/title and asks before\n\
+        deleting; add --yes to skip the question.\n\n\
+        --stdin deletes every ID piped in (one per line, or a JSON array of IDs or\n\
+        objects with an id), reporting each and continuing past failures unless\n\
+        --stop-on-error is given.\n\n\
+        Examples:\n\
+        arky provider delete PROVIDER_ID --show\n\
+        arky provider list --all --pointer /data | arky provider delete --stdin")]
     Delete {
         /// Provider ID
+        #[arg(required_unless_present = "stdin", default_value = "")]
         id: String,
+        #[command(flatten)]
+        delete: DeleteArgs,
     },
     /// Get a provider's working time for a service
     #[command(long_about = "Fetch the working time schedule for a provider.\n\n\
@@ -108,9 +173,34 @@ pub enum ProviderCommand {
         #[arg(long)]
         service_id: Option<String>,
     },
+    /// Replace a provider's working time for one service
+    #[command(name = "set-working-time", long_about = "Set the schedule a provider offers for one service.\n\n\
+        Fetches the service, replaces workingTime on this provider's entry in the\n\
+        providers array, and PUTs the full service back (other providers untouched).\n\
+        The schedule is validated first: day names, from < to, 0-1440 minutes.\n\n\
+        Required:\n\
+          --service-id   Service the provider is attached to\n\
+          --data         workingTime object\n\n\
+        Example:\n\
+        arky provider set-working-time PROV_ID --service-id SVC_ID --data '{\n\
+          \"workingDays\": [{\"day\": \"monday\", \"workingHours\": [{\"from\": 540, \"to\": 1020}]}],\n\
+          \"outcastDates\": [],\n\
+          \"specificDates\": []\n\
+        }'")]
+    SetWorkingTime {
+        /// Provider ID
+        provider_id: String,
+        #[arg(long)]
+        service_id: String,
+        #[arg(long, help = "workingTime JSON: inline, @file, or - for stdin")]
+        data: Option<String>,
+    },
 }
 
 pub async fn handle(cmd: ProviderCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    if cmd.is_example() {
+        return print_example(&example());
+    }
     let biz_id = client.require_business_id()?;
 
     match cmd {
@@ -118,58 +208,113 @@ pub async fn handle(cmd: ProviderCommand, client: &ArkyClient, format: &Format)
             let result = client
                 .get(&format!("/v1/businesses/{biz_id}/providers/{id}"), &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         ProviderCommand::List {
             query,
             service_id,
-            limit,
-            cursor,
+            available_at,
+            utc_offset,
+            list,
             statuses,
         } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
             if let Some(ref s) = service_id {
                 params.push(("serviceId", s.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             if let Some(ref s) = statuses {
                 params.push(("statuses", s.clone()));
             }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(&format!("/v1/businesses/{biz_id}/providers"), &params_ref)
+            let path = format!("/v1/businesses/{biz_id}/providers");
+            let (Some(at), Some(service_id)) = (available_at, service_id) else {
+                let result = fetch_list(client, &path, &params_ref, &list).await?;
+                crate::output::print_output(&result, format)?;
+                return Ok(());
+            };
+            // Free providers can be on any page, so filter them all
+            let every_page = ListArgs {
+                all: true,
+                count: false,
+                limit: None,
+                ..list.clone()
+            };
+            let mut result = fetch_list(client, &path, &params_ref, &every_page).await?;
+            let at = parse_time_arg(&at)?;
+            let service = client
+                .get(&format!("/v1/businesses/{biz_id}/services/{service_id}"), &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            let (from, to) = (at.to_string(), (at + 1).to_string());
+            let window = [("from", from.as_str()), ("to", to.as_str())];
+            let bookings =
+                fetch_all(client, &format!("/v1/businesses/{biz_id}/bookings"), &window).await?;
+            let local = at + utc_offset * 60;
+            let items = result["data"].as_array_mut().map(std::mem::take).unwrap_or_default();
+            let mut free: Vec<Value> = items
+                .into_iter()
+                .filter(|p| provider_free(p, &service, &bookings, at, local))
+                .collect();
+            if list.count {
+                result = json!(free.len());
+            } else {
+                if let Some(limit) = list.limit.filter(|n| *n > 0) {
+                    free.truncate(limit as usize);
+                }
+                result["data"] = json!(free);
+            }
+            crate::output::print_output(&result, format)?;
         }
-        ProviderCommand::Create { key, data } => {
-            let mut body = json!({ "key": key });
-            let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
-            let result = client
-                .post(&format!("/v1/businesses/{biz_id}/providers"), &body)
-                .await?;
-            crate::output::print_output(&result, format);
+        ProviderCommand::Create {
+            key,
+            data,
+            id_only,
+            upsert,
+            ..
+        } => {
+            let bodies = keyed_records(key, parse_records(data.as_deref())?)?;
+            for body in &bodies {
+                check_known_fields(body, PROVIDER_FIELDS, "provider")?;
+            }
+            let path = format!("/v1/businesses/{biz_id}/providers");
+            let total = bodies.len();
+            for (i, body) in bodies.iter().enumerate() {
+                let result = create_or_update(client, &path, "provider", body, upsert)
+                    .await
+                    .inspect_err(|_| note_failed_record(i, total))?;
+                print_created(&result, "provider", id_only, format)?;
+            }
         }
-        ProviderCommand::Update { id, data } => {
+        ProviderCommand::Update {
+            id, data, force, ..
+        } => {
             let mut body = json!({ "id": id });
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
-            let result = client
-                .put(&format!("/v1/businesses/{biz_id}/providers/{id}"), &body)
-                .await?;
-            crate::output::print_output(&result, format);
+            check_known_fields(&overlay, PROVIDER_FIELDS, "provider")?;
+            merge_data(&mut body, overlay)?;
+            let path = format!("/v1/businesses/{biz_id}/providers/{id}");
+            if !force {
+                preserve_required_fields(client, &path, &mut body, PROVIDER_REQUIRED_FIELDS)
+                    .await?;
+            }
+            let result = client.put(&path, &body).await?;
+            crate::output::print_output(&result, format)?;
         }
-        ProviderCommand::Delete { id } => {
-            let _ = client
-                .delete(&format!("/v1/businesses/{biz_id}/providers/{id}"))
-                .await?;
-            crate::output::print_success("Provider deleted");
+        ProviderCommand::Delete { id, delete } => {
+            let collection = format!("/v1/businesses/{biz_id}/providers");
+            if delete.stdin {
+                return delete_from_stdin(client, &collection, "provider", &delete).await;
+            }
+            let path = format!("{collection}/{id}");
+            if !confirm_delete(client, &path, "provider", &delete, format).await? {
+                return Ok(());
+            }
+            if delete_entity(client, &path, "provider", delete.ignore_missing).await? {
+                crate::output::print_success("Provider deleted");
+            }
         }
         ProviderCommand::WorkingTime {
             provider_id,
@@ -187,8 +332,150 @@ pub async fn handle(cmd: ProviderCommand, client: &ArkyClient, format: &Format)
                     &params_ref,
                 )
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
+        }
+        ProviderCommand::SetWorkingTime {
+            provider_id,
+            service_id,
+            data,
+        } => {
+            let working_time = parse_data(data.as_deref())?;
+            validate_working_time(&working_time)?;
+
+            let path = format!("/v1/businesses/{biz_id}/services/{service_id}");
+            let mut service = client.get(&path, &[]).await?;
+            let entry = service["providers"]
+                .as_array_mut()
+                .and_then(|providers| {
+                    providers
+                        .iter_mut()
+                        .find(|p| p["providerId"] == provider_id.as_str())
+                })
+                .ok_or_else(|| {
+                    CliError::InvalidInput(format!(
+                        "Provider {provider_id} is not attached to service {service_id}"
+                    ))
+                })?;
+            entry["workingTime"] = working_time;
+
+            let result = client.put(&path, &service).await?;
+            crate::output::print_output(&result, format)?;
         }
     }
     Ok(())
 }
+
+fn example() -> Value {
+    json!({
+        "slug": { "en": "john" },
+        "status": "active",
+        "networkIds": [],
+        "audienceIds": [],
+        "filters": [],
+        "blocks": [
+            {
+                "type": "localized_text",
+                "id": "b1",
+                "key": "name",
+                "properties": {},
+                "value": { "en": "John" }
+            }
+        ],
+        "concurrentLimit": 1
+    })
+}
+
+/// Whether `provider` can take a booking of `service` at `at`: the local time
+/// (`local`, epoch seconds shifted to the business zone) is inside its weekly
+/// working hours, and overlapping bookings are below its concurrentLimit
+fn provider_free(
+    provider: &Value,
+    service: &Value,
+    bookings: &[Value],
+    at: i64,
+    local: i64,
+) -> bool {
+    let Some(id) = provider["id"].as_str() else {
+        return false;
+    };
+    let entry = service["providers"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|p| p["providerId"] == id);
+    if !entry.is_some_and(|e| works_at(&e["workingTime"], local)) {
+        return false;
+    }
+    let limit = provider["concurrentLimit"].as_u64().unwrap_or(1) as usize;
+    let overlapping = bookings
+        .iter()
+        .filter(|b| b["status"].as_str().is_none_or(|s| !RELEASED_BOOKING_STATUSES.contains(&s)))
+        .flat_map(|b| b["items"].as_array().into_iter().flatten())
+        .filter(|item| item["providerId"] == id)
+        .filter(|item| {
+            let (from, to) = (item["from"].as_i64(), item["to"].as_i64());
+            from.is_some_and(|f| f <= at) && to.is_some_and(|t| at < t)
+        })
+        .count();
+    overlapping < limit
+}
+
+/// Whether a weekly `workingTime` covers `local` (epoch seconds in local time)
+fn works_at(working_time: &Value, local: i64) -> bool {
+    let days = local.div_euclid(86_400);
+    // 1970-01-01 was a Thursday (index 3 from monday)
+    let weekday = WEEKDAYS[(days + 3).rem_euclid(7) as usize];
+    let minute = local.rem_euclid(86_400) / 60;
+    working_time["workingDays"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|d| d["day"] == weekday)
+        .flat_map(|d| d["workingHours"].as_array().into_iter().flatten())
+        .any(|h| {
+            h["from"].as_i64().is_some_and(|f| f <= minute)
+                && h["to"].as_i64().is_some_and(|t| minute < t)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Monday 2025-01-06 10:00 UTC
+    const MONDAY_10AM: i64 = 1_736_157_600;
+
+    fn service() -> Value {
+        json!({"providers": [{
+            "providerId": "p1",
+            "workingTime": {"workingDays": [
+                {"day": "monday", "workingHours": [{"from": 540, "to": 1020}]}
+            ]}
+        }]})
+    }
+
+    #[test]
+    fn test_works_at() {
+        let working_time = &service()["providers"][0]["workingTime"];
+        assert!(works_at(working_time, MONDAY_10AM));
+        assert!(!works_at(working_time, MONDAY_10AM - 2 * 3600)); // 8:00
+        assert!(!works_at(working_time, MONDAY_10AM + 86_400)); // tuesday
+    }
+
+    #[test]
+    fn test_provider_free() {
+        let provider = json!({"id": "p1", "concurrentLimit": 1});
+        let at = MONDAY_10AM;
+        assert!(provider_free(&provider, &service(), &[], at, at));
+        assert!(!provider_free(&json!({"id": "p2"}), &service(), &[], at, at));
+
+        let booked = json!({"status": "confirmed", "items": [
+            {"providerId": "p1", "from": at - 1800, "to": at + 1800}
+        ]});
+        assert!(!provider_free(&provider, &service(), std::slice::from_ref(&booked), at, at));
+        let cancelled = json!({"status": "cancelled", "items": booked["items"].clone()});
+        assert!(provider_free(&provider, &service(), &[cancelled], at, at));
+        let two_at_once = json!({"id": "p1", "concurrentLimit": 2});
+        assert!(provider_free(&two_at_once, &service(), &[booked], at, at));
+    }
+}
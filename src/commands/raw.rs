@@ -0,0 +1,126 @@
+use crate::client::ArkyClient;
+use crate::commands::parse_data;
+use crate::error::{CliError, Result};
+use crate::output::Format;
+use clap::Args;
+use reqwest::Method;
+
+/// Call any API endpoint directly
+#[derive(Args, Debug)]
+pub struct RawCommand {
+    /// HTTP method: GET, POST, PUT, PATCH, DELETE
+    pub method: String,
+    /// API path, e.g. /v1/businesses/{biz}/nodes ({biz} = current business ID)
+    pub path: String,
+    #[arg(long, help = "JSON body: inline, @file, or - for stdin")]
+    pub data: Option<String>,
+    #[arg(long, help = "Query parameter as key=value (repeatable)")]
+    pub query: Vec<String>,
+}
+
+pub async fn handle(cmd: RawCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    let method = parse_method(&cmd.method)?;
+    let path = resolve_path(&cmd.path, client)?;
+
+    let params: Vec<(String, String)> = cmd
+        .query
+        .iter()
+        .map(|q| parse_query(q))
+        .collect::<Result<_>>()?;
+    let params_ref: Vec<(&str, &str)> = params
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+
+    let body = match cmd.data {
+        Some(ref d) => Some(parse_data(Some(d))?),
+        None => None,
+    };
+    if body.is_some() && matches!(method, Method::GET | Method::DELETE) {
+        return Err(CliError::InvalidInput(format!(
+            "--data is not allowed with {method}"
+        )));
+    }
+
+    let result = client
+        .request(method, &path, &params_ref, body.as_ref())
+        .await?;
+    crate::output::print_output(&result, format)?;
+    Ok(())
+}
+
+fn parse_method(method: &str) -> Result<Method> {
+    match method.to_uppercase().as_str() {
+        "GET" => Ok(Method::GET),
+        "POST" => Ok(Method::POST),
+        "PUT" => Ok(Method::PUT),
+        "PATCH" => Ok(Method::PATCH),
+        "DELETE" => Ok(Method::DELETE),
+        _ => Err(CliError::InvalidInput(format!(
+            "Unsupported method: {method}. Use GET, POST, PUT, PATCH or DELETE"
+        ))),
+    }
+}
+
+fn resolve_path(path: &str, client: &ArkyClient) -> Result<String> {
+    if !path.starts_with('/') || path.chars().any(char::is_whitespace) {
+        return Err(CliError::InvalidInput(format!(
+            "Invalid path: {path}. Paths must start with / and contain no spaces (e.g. /v1/platform/currencies)"
+        )));
+    }
+    if path.contains("{biz}") {
+        let biz_id = client.require_business_id()?;
+        Ok(path.replace("{biz}", biz_id))
+    } else {
+        Ok(path.to_string())
+    }
+}
+
+fn parse_query(pair: &str) -> Result<(String, String)> {
+    match pair.split_once('=') {
+        Some((k, v)) if !k.is_empty() => Ok((k.to_string(), v.to_string())),
+        _ => Err(CliError::InvalidInput(format!(
+            "Invalid --query '{pair}'. Expected key=value"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_method() {
+        assert_eq!(parse_method("get").unwrap(), Method::GET);
+        assert_eq!(parse_method("PATCH").unwrap(), Method::PATCH);
+        assert!(parse_method("TRACE").is_err());
+    }
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(
+            parse_query("limit=5").unwrap(),
+            ("limit".to_string(), "5".to_string())
+        );
+        assert_eq!(
+            parse_query("q=a=b").unwrap(),
+            ("q".to_string(), "a=b".to_string())
+        );
+        assert!(parse_query("novalue").is_err());
+        assert!(parse_query("=x").is_err());
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let client = ArkyClient::new("http://localhost".into(), Some("biz_1".into()), None);
+        assert_eq!(
+            resolve_path("/v1/businesses/{biz}/nodes", &client).unwrap(),
+            "/v1/businesses/biz_1/nodes"
+        );
+        assert!(resolve_path("v1/platform", &client).is_err());
+        assert!(resolve_path("/v1/a b", &client).is_err());
+
+        let no_biz = ArkyClient::new("http://localhost".into(), None, None);
+        assert!(resolve_path("/v1/businesses/{biz}", &no_biz).is_err());
+    }
+}
@@ -1,9 +1,20 @@
 use crate::client::ArkyClient;
-use crate::commands::{merge_data, parse_data};
-use crate::error::Result;
+use crate::commands::business::BusinessConfigs;
+use crate::commands::{
+    check_known_fields, confirm_delete, create_or_update, delete_entity, delete_from_stdin,
+    find_entry, keyed_records, merge_data, note_failed_record, parse_data, parse_records,
+    preserve_required_fields, print_created, print_example, print_list, regenerate_block_ids,
+    set_price, DeleteArgs, Example, ListArgs, SERVICE_REQUIRED_FIELDS,
+};
+use crate::error::{CliError, Result};
 use crate::output::Format;
 use clap::Subcommand;
-use serde_json::json;
+use serde_json::{json, Value};
+
+/// Top-level service fields accepted in --data (checked with --strict)
+const SERVICE_FIELDS: &[&str] = &[
+    "key", "slug", "status", "audienceIds", "networkIds", "filters", "blocks", "providers",
+];
 
 #[derive(Subcommand, Debug)]
 pub enum ServiceCommand {
@@ -22,19 +33,21 @@ pub enum ServiceCommand {
     },
     /// List services
     #[command(long_about = "List bookable services.\n\n\
+        Optionally filter by provider to see which services a provider offers.\n\n\
         Examples:\n\
         arky service list\n\
-        arky service list --query \"hair\" --statuses active\n\n\
+        arky service list --query \"hair\" --statuses active\n\
+        arky service list --provider-id PROV_ID\n\n\
         Response: {\"data\": [...], \"cursor\": \"...\"}")]
     List {
         #[arg(long)]
         query: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
         #[arg(long, help = "Comma-separated: draft,active,archived")]
         statuses: Option<String>,
+        #[arg(long, help = "Filter services by provider ID")]
+        provider_id: Option<String>,
     },
     /// Create a service with blocks, providers, and working time
     #[command(long_about = "Create a bookable service.\n\n\
@@ -58,7 +71,7 @@ pub enum ServiceCommand {
     Working time structure:\n\
       workingDays: [{\"day\": \"monday\", \"workingHours\": [{\"from\": 540, \"to\": 1020}]}]\n\
         day: monday|tuesday|wednesday|thursday|friday|saturday|sunday\n\
-        from/to: minutes from midnight (540 = 9:00 AM, 1020 = 5:00 PM)\n\
+        from/to: minutes from midnight (540 = 9:00 AM, 1020 = 5:00 PM), from < to, max 1440\n\
       outcastDates: [] — holidays/blocked dates\n\
       specificDates: [] — overrides\n\n\
     Common time values (minutes from midnight):\n\
@@ -94,12 +107,22 @@ pub enum ServiceCommand {
           \"specificDates\": []\n\
         }\n\
       }]\n\
-    }'")]
+    }'\n\n\
+    Batch create (NDJSON):\n\
+      Several newline-delimited JSON objects in --data create one service each,\n\
+      in order. Omit KEY and give each record its own \"key\".\n\
+      cat services.ndjson | arky service create --data -")]
     Create {
-        /// Service key (unique within business, URL-safe)
-        key: String,
+        /// Service key (unique within business, URL-safe); omit for multi-record --data
+        key: Option<String>,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Print only the created ID, e.g. for ID=$(arky ... --id-only)")]
+        id_only: bool,
+        #[arg(long, help = "If KEY is taken, update that service instead of failing")]
+        upsert: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Update a service
     #[command(long_about = "Update a service by ID.\n\n\
@@ -110,21 +133,89 @@ pub enum ServiceCommand {
           status     \"draft\" | \"active\" | \"archived\"\n\n\
         Example:\n\
         arky service update SVC_ID --data '{\"blocks\": [...], \"providers\": [...]}'\n\
-        arky service update SVC_ID --data '{\"status\": \"active\"}'")]
+        arky service update SVC_ID --data '{\"status\": \"active\"}'\n\n\
+        Preserved fields:\n\
+          key, slug, status, audienceIds, networkIds, filters, blocks and providers\n\
+          are copied from the current service when missing from --data.\n\
+          Use --force to send the payload as-is.\n\n\
+        Partial updates:\n\
+          --patch sends only the given fields via PATCH (if the server supports it).\n\
+          arky service update SVC_ID --patch --data '{\"status\": \"active\"}'")]
     Update {
         /// Service ID
+        #[arg(required_unless_present = "example", default_value = "")]
         id: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Send only the given fields via PATCH instead of a full PUT")]
+        patch: bool,
+        #[arg(long, help = "Don't fill in required fields missing from --data")]
+        force: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
+    },
+    /// Set one provider's price for a market and currency
+    #[command(long_about = "Set the price a service charges with one provider without resending\n\
+        the whole providers array. The service is fetched, the provider's price\n\
+        for --market/--currency is updated (or added if missing), and the\n\
+        service is saved back.\n\n\
+        --amount is in minor units (cents). The market and currency are checked\n\
+        against the business configs; use --no-validate to skip the check.\n\n\
+        Example:\n\
+        arky service set-price SVC_ID --provider PROV_ID --market us --currency usd --amount 5000")]
+    SetPrice {
+        /// Service ID
+        id: String,
+        #[arg(long, help = "Provider ID (an entry of the service's providers)")]
+        provider: String,
+        #[arg(long)]
+        market: String,
+        #[arg(long)]
+        currency: String,
+        #[arg(long, help = "Price in minor units (cents)")]
+        amount: u64,
+        #[arg(long, help = "Skip checking the market/currency against business configs")]
+        no_validate: bool,
     },
     /// Delete a service
+    #[command(long_about = "Delete a service by ID.\n\n\
+        --show fetches it first, prints its id/key/status/title and asks before\n\
+        deleting; add --yes to skip the question.\n\n\
+        --stdin deletes every ID piped in (one per line, or a JSON array of IDs or\n\
+        objects with an id), reporting each and continuing past failures unless\n\
+        --stop-on-error is given.\n\n\
+        Examples:\n\
+        arky service delete SERVICE_ID --show\n\
+        arky service list --all --pointer /data | arky service delete --stdin")]
     Delete {
         /// Service ID
+        #[arg(required_unless_present = "stdin", default_value = "")]
         id: String,
+        #[command(flatten)]
+        delete: DeleteArgs,
+    },
+    /// Duplicate a service under a new key
+    #[command(long_about = "Copy an existing service (blocks, providers, working time) to a new key.\n\n\
+        Server-managed fields (id, businessId, timestamps) are dropped, block IDs are\n\
+        regenerated, and every slug locale is set to the new key.\n\
+        Optional --data is merged on top (top-level fields replace the copied ones).\n\n\
+        Examples:\n\
+        arky service clone SVC_ID haircut-60\n\
+        arky service clone SVC_ID haircut-30 --data '{\"status\": \"draft\"}'")]
+    Clone {
+        /// Source service ID
+        source_id: String,
+        /// Key for the new service
+        new_key: String,
+        #[arg(long, help = "JSON overrides: inline, @file, or - for stdin")]
+        data: Option<String>,
     },
 }
 
 pub async fn handle(cmd: ServiceCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    if cmd.is_example() {
+        return print_example(&example());
+    }
     let biz_id = client.require_business_id()?;
 
     match cmd {
@@ -132,55 +223,299 @@ pub async fn handle(cmd: ServiceCommand, client: &ArkyClient, format: &Format) -
             let result = client
                 .get(&format!("/v1/businesses/{biz_id}/services/{id}"), &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         ServiceCommand::List {
             query,
-            limit,
-            cursor,
+            list,
             statuses,
+            provider_id,
         } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             if let Some(ref s) = statuses {
                 params.push(("statuses", s.clone()));
             }
+            if let Some(ref p) = provider_id {
+                params.push(("providerId", p.clone()));
+            }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(&format!("/v1/businesses/{biz_id}/services"), &params_ref)
-                .await?;
-            crate::output::print_output(&result, format);
+            print_list(
+                client,
+                &format!("/v1/businesses/{biz_id}/services"),
+                &params_ref,
+                &list,
+                format,
+            )
+            .await?;
+        }
+        ServiceCommand::Create {
+            key,
+            data,
+            id_only,
+            upsert,
+            ..
+        } => {
+            let bodies = keyed_records(key, parse_records(data.as_deref())?)?;
+            for body in &bodies {
+                check_known_fields(body, SERVICE_FIELDS, "service")?;
+                validate_service_providers(body)?;
+            }
+            let path = format!("/v1/businesses/{biz_id}/services");
+            let total = bodies.len();
+            for (i, body) in bodies.iter().enumerate() {
+                let result = create_or_update(client, &path, "service", body, upsert)
+                    .await
+                    .inspect_err(|_| note_failed_record(i, total))?;
+                print_created(&result, "service", id_only, format)?;
+            }
         }
-        ServiceCommand::Create { key, data } => {
-            let mut body = json!({ "key": key });
+        ServiceCommand::Update {
+            id,
+            data,
+            patch,
+            force,
+            ..
+        } => {
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
-            let result = client
-                .post(&format!("/v1/businesses/{biz_id}/services"), &body)
-                .await?;
-            crate::output::print_output(&result, format);
+            check_known_fields(&overlay, SERVICE_FIELDS, "service")?;
+            validate_service_providers(&overlay)?;
+            let path = format!("/v1/businesses/{biz_id}/services/{id}");
+            let result = if patch {
+                client.patch(&path, &overlay).await?
+            } else {
+                let mut body = json!({ "id": id });
+                merge_data(&mut body, overlay)?;
+                if !force {
+                    preserve_required_fields(client, &path, &mut body, SERVICE_REQUIRED_FIELDS).await?;
+                }
+                client.put(&path, &body).await?
+            };
+            crate::output::print_output(&result, format)?;
+        }
+        ServiceCommand::SetPrice {
+            id,
+            provider,
+            market,
+            currency,
+            amount,
+            no_validate,
+        } => {
+            if !no_validate {
+                let mut configs = BusinessConfigs::new(client);
+                configs.check_market(&market).await?;
+                configs.check_currency(&currency).await?;
+            }
+            let path = format!("/v1/businesses/{biz_id}/services/{id}");
+            let mut service = client.get(&path, &[]).await?;
+            let entry = find_entry(&mut service, "providers", "providerId", &provider)?;
+            set_price(&mut entry["prices"], &market, &currency, amount);
+            let result = client.put(&path, &service).await?;
+            crate::output::print_output(&result, format)?;
+        }
+        ServiceCommand::Delete { id, delete } => {
+            let collection = format!("/v1/businesses/{biz_id}/services");
+            if delete.stdin {
+                return delete_from_stdin(client, &collection, "service", &delete).await;
+            }
+            let path = format!("{collection}/{id}");
+            if !confirm_delete(client, &path, "service", &delete, format).await? {
+                return Ok(());
+            }
+            if delete_entity(client, &path, "service", delete.ignore_missing).await? {
+                crate::output::print_success("Service deleted");
+            }
         }
-        ServiceCommand::Update { id, data } => {
-            let mut body = json!({ "id": id });
+        ServiceCommand::Clone {
+            source_id,
+            new_key,
+            data,
+        } => {
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
+            let mut body = client
+                .get(&format!("/v1/businesses/{biz_id}/services/{source_id}"), &[])
+                .await?;
+            if let Some(obj) = body.as_object_mut() {
+                for field in ["id", "businessId", "createdAt", "updatedAt"] {
+                    obj.remove(field);
+                }
+                obj.insert("key".into(), json!(new_key));
+                if let Some(slug) = obj.get_mut("slug").and_then(|s| s.as_object_mut()) {
+                    for locale in slug.values_mut() {
+                        *locale = json!(new_key);
+                    }
+                }
+                if let Some(providers) = obj.get_mut("providers").and_then(|p| p.as_array_mut()) {
+                    for provider in providers.iter_mut().filter_map(|p| p.as_object_mut()) {
+                        provider.remove("id");
+                    }
+                }
+            }
+            if let Some(blocks) = body.get_mut("blocks") {
+                regenerate_block_ids(blocks);
+            }
+            merge_data(&mut body, overlay)?;
             let result = client
-                .put(&format!("/v1/businesses/{biz_id}/services/{id}"), &body)
+                .post(&format!("/v1/businesses/{biz_id}/services"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
-        ServiceCommand::Delete { id } => {
-            let _ = client
-                .delete(&format!("/v1/businesses/{biz_id}/services/{id}"))
-                .await?;
-            crate::output::print_success("Service deleted");
+    }
+    Ok(())
+}
+
+fn example() -> Value {
+    json!({
+        "slug": { "en": "haircut" },
+        "status": "active",
+        "networkIds": [],
+        "audienceIds": [],
+        "filters": [],
+        "blocks": [
+            {
+                "type": "localized_text",
+                "id": "b1",
+                "key": "title",
+                "properties": {},
+                "value": { "en": "Haircut" }
+            }
+        ],
+        "providers": [{
+            "providerId": "PROVIDER_ID",
+            "prices": [{ "currency": "usd", "market": "us", "amount": 5000 }],
+            "durations": [{ "duration": 60, "isPause": false }],
+            "isApprovalRequired": false,
+            "audienceIds": [],
+            "workingTime": {
+                "workingDays": [
+                    { "day": "monday", "workingHours": [{ "from": 540, "to": 1020 }] }
+                ],
+                "outcastDates": [],
+                "specificDates": []
+            }
+        }]
+    })
+}
+
+pub const WEEKDAYS: &[&str] = &[
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// Minutes in a day: working hours are minutes from midnight (540 = 9:00 AM)
+pub const MINUTES_PER_DAY: i64 = 24 * 60;
+
+/// Check a provider `workingTime` object before sending it
+pub fn validate_working_time(working_time: &Value) -> Result<()> {
+    check_working_time(working_time, "workingTime")
+}
+
+/// Check the workingTime of every entry in a service's providers array
+pub fn validate_service_providers(body: &Value) -> Result<()> {
+    for (i, provider) in body["providers"].as_array().into_iter().flatten().enumerate() {
+        if !provider["workingTime"].is_null() {
+            check_working_time(
+                &provider["workingTime"],
+                &format!("providers[{i}].workingTime"),
+            )?;
         }
     }
     Ok(())
 }
+
+fn check_working_time(working_time: &Value, path: &str) -> Result<()> {
+    let invalid = |at: String, msg: String| Err(CliError::InvalidInput(format!("{at}: {msg}")));
+
+    let Some(days) = working_time["workingDays"].as_array() else {
+        return invalid(format!("{path}.workingDays"), "must be an array".into());
+    };
+    for (d, day) in days.iter().enumerate() {
+        let day_path = format!("{path}.workingDays[{d}]");
+        let name = day["day"].as_str().unwrap_or_default();
+        if !WEEKDAYS.contains(&name) {
+            return invalid(
+                format!("{day_path}.day"),
+                format!("unknown day {}. Use one of: {}", day["day"], WEEKDAYS.join(", ")),
+            );
+        }
+        let Some(hours) = day["workingHours"].as_array() else {
+            return invalid(format!("{day_path}.workingHours"), "must be an array".into());
+        };
+        for (h, range) in hours.iter().enumerate() {
+            let at = format!("{day_path}.workingHours[{h}]");
+            let (Some(from), Some(to)) = (range["from"].as_i64(), range["to"].as_i64()) else {
+                return invalid(at, "from/to must be whole minutes from midnight".into());
+            };
+            for value in [from, to] {
+                if !(0..=MINUTES_PER_DAY).contains(&value) {
+                    return invalid(
+                        at,
+                        format!(
+                            "{value} is out of range 0-{MINUTES_PER_DAY} (minutes from midnight, \
+                             e.g. 540 = 9:00 AM, 1020 = 5:00 PM)"
+                        ),
+                    );
+                }
+            }
+            if from >= to {
+                return invalid(at, format!("from ({from}) must be before to ({to})"));
+            }
+        }
+    }
+    for key in ["outcastDates", "specificDates"] {
+        if !working_time[key].is_null() && !working_time[key].is_array() {
+            return invalid(format!("{path}.{key}"), "must be an array".into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(day: &str, from: i64, to: i64) -> Value {
+        json!({
+            "workingDays": [{"day": day, "workingHours": [{"from": from, "to": to}]}],
+            "outcastDates": [],
+            "specificDates": []
+        })
+    }
+
+    #[test]
+    fn test_valid_working_time() {
+        assert!(validate_working_time(&schedule("monday", 540, 1020)).is_ok());
+        assert!(validate_working_time(&schedule("sunday", 0, 1440)).is_ok());
+        assert!(validate_working_time(&json!({"workingDays": []})).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_working_time() {
+        let err = validate_working_time(&schedule("funday", 540, 1020)).unwrap_err();
+        assert!(err.to_string().contains("workingTime.workingDays[0].day"));
+        let err = validate_working_time(&schedule("monday", 1020, 540)).unwrap_err();
+        assert!(err.to_string().contains("must be before"));
+        let err = validate_working_time(&schedule("monday", 540, 32_400_000)).unwrap_err();
+        assert!(err.to_string().contains("out of range"));
+        assert!(validate_working_time(&json!({})).is_err());
+        assert!(validate_working_time(&json!({"workingDays": [], "outcastDates": {}})).is_err());
+    }
+
+    #[test]
+    fn test_validate_service_providers_reports_path() {
+        let body = json!({"providers": [
+            {"providerId": "p1", "workingTime": schedule("monday", 540, 1020)},
+            {"providerId": "p2", "workingTime": schedule("tuesday", 600, 600)}
+        ]});
+        let err = validate_service_providers(&body).unwrap_err();
+        assert!(err.to_string().contains("providers[1].workingTime.workingDays[0].workingHours[0]"));
+    }
+}
@@ -1,8 +1,12 @@
 use crate::client::ArkyClient;
-use crate::commands::parse_data;
-use crate::error::Result;
+use crate::commands::{merge_data, page_items, parse_data, validate_address};
+use crate::error::{CliError, Result};
 use crate::output::Format;
 use clap::Subcommand;
+use serde_json::{json, Value};
+
+/// Address fields of a shipping request, validated locally before sending
+const SHIPPING_ADDRESS_FIELDS: [&str; 2] = ["fromAddress", "toAddress"];
 
 #[derive(Subcommand, Debug)]
 pub enum ShippingCommand {
@@ -18,7 +22,8 @@ pub enum ShippingCommand {
           toAddress            Destination address\n\
           parcel               Package dimensions and weight\n\n\
         Address fields: name, street1, street2 (optional), city, state,\n\
-        postalCode, country (ISO 2-letter).\n\n\
+        postalCode, country (ISO 2-letter). Both addresses are checked for\n\
+        these before the request is sent.\n\n\
         Parcel fields: length, width, height, weight,\n\
         distanceUnit (in|cm), massUnit (oz|g|lb|kg).\n\n\
         Example:\n\
@@ -37,6 +42,9 @@ pub enum ShippingCommand {
             \"distanceUnit\": \"in\", \"massUnit\": \"oz\"\n\
           }\n\
         }'\n\n\
+        With --save the rates are kept in ~/.arky/cache for this order, so\n\
+        `arky shipping ship ORDER_ID --rate RATE_ID` (or --cheapest/--fastest)\n\
+        can fill in rateId, carrier and service.\n\n\
         Response shape:\n\
         [{\"rateId\": \"rate_abc\", \"carrier\": \"usps\", \"service\": \"usps_priority\",\n\
           \"amount\": 795, \"currency\": \"USD\", \"estimatedDays\": 3}]")]
@@ -45,6 +53,8 @@ pub enum ShippingCommand {
         order_id: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Save the rates for `shipping ship --rate/--cheapest/--fastest`")]
+        save: bool,
     },
     /// Ship an order: create shipment + purchase label
     #[command(long_about = "Create a shipment and purchase a shipping label.\n\n\
@@ -57,12 +67,23 @@ pub enum ShippingCommand {
           \"locationId\": \"loc_123\",\n\
           \"lines\": [{\"orderItemId\": \"item_1\", \"quantity\": 1}]\n\
         }'\n\n\
+        With rates saved by `arky shipping rates ORDER_ID --save`, pick one with\n\
+        --rate RATE_ID, --cheapest or --fastest instead of copying rateId,\n\
+        carrier and service (values in --data still win):\n\
+        arky shipping ship ORDER_ID --cheapest \\\n\
+          --data '{\"locationId\": \"loc_123\", \"lines\": [...]}'\n\n\
         Response includes: trackingNumber, labelUrl, carrier, service.")]
     Ship {
         /// Order ID
         order_id: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, group = "saved_rate", help = "Use this rate ID from the saved rates")]
+        rate: Option<String>,
+        #[arg(long, group = "saved_rate", help = "Use the lowest-priced saved rate")]
+        cheapest: bool,
+        #[arg(long, group = "saved_rate", help = "Use the saved rate with the fewest days")]
+        fastest: bool,
     },
 }
 
@@ -70,26 +91,112 @@ pub async fn handle(cmd: ShippingCommand, client: &ArkyClient, format: &Format)
     let biz_id = client.require_business_id()?;
 
     match cmd {
-        ShippingCommand::Rates { order_id, data } => {
+        ShippingCommand::Rates {
+            order_id,
+            data,
+            save,
+        } => {
             let body = parse_data(data.as_deref())?;
+            for label in SHIPPING_ADDRESS_FIELDS {
+                let address = body.get(label).ok_or_else(|| {
+                    CliError::InvalidInput(format!("--data is missing {label}"))
+                })?;
+                validate_address(address, label)?;
+            }
             let result = client
                 .post(
                     &format!("/v1/businesses/{biz_id}/orders/{order_id}/shipping/rates"),
                     &body,
                 )
                 .await?;
-            crate::output::print_output(&result, format);
+            if save {
+                crate::cache::store(&rates_cache_key(&order_id), &result)?;
+            }
+            crate::output::print_output(&result, format)?;
         }
-        ShippingCommand::Ship { order_id, data } => {
-            let body = parse_data(data.as_deref())?;
+        ShippingCommand::Ship {
+            order_id,
+            data,
+            rate,
+            cheapest,
+            fastest,
+        } => {
+            let overlay = parse_data(data.as_deref())?;
+            let body = if rate.is_some() || cheapest || fastest {
+                let saved = crate::cache::load(&rates_cache_key(&order_id), None).ok_or_else(|| {
+                    CliError::InvalidInput(format!(
+                        "No saved rates for order {order_id}. \
+                         Run `arky shipping rates {order_id} --save --data ...` first"
+                    ))
+                })?;
+                let chosen = select_rate(&saved, rate.as_deref(), fastest)?;
+                let mut body = json!({
+                    "rateId": chosen["rateId"],
+                    "carrier": chosen["carrier"],
+                    "service": chosen["service"],
+                });
+                merge_data(&mut body, overlay)?;
+                body
+            } else {
+                overlay
+            };
+            for label in SHIPPING_ADDRESS_FIELDS {
+                if let Some(address) = body.get(label) {
+                    validate_address(address, label)?;
+                }
+            }
             let result = client
                 .post(
                     &format!("/v1/businesses/{biz_id}/orders/{order_id}/ship"),
                     &body,
                 )
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
     }
     Ok(())
 }
+
+fn rates_cache_key(order_id: &str) -> String {
+    format!("shipping-rates-{order_id}")
+}
+
+/// Pick a saved rate: by ID when given, else the fastest (fewest
+/// `estimatedDays`, cheaper first on ties) or the cheapest (lowest `amount`)
+fn select_rate<'a>(saved: &'a Value, rate_id: Option<&str>, fastest: bool) -> Result<&'a Value> {
+    let rates = page_items(saved).map(Vec::as_slice).unwrap_or_default();
+    let amount = |r: &Value| r["amount"].as_f64().unwrap_or(f64::MAX);
+    let days = |r: &Value| r["estimatedDays"].as_u64().unwrap_or(u64::MAX);
+    let chosen = match rate_id {
+        Some(id) => rates.iter().find(|r| r["rateId"] == id),
+        None if fastest => rates
+            .iter()
+            .min_by(|a, b| days(a).cmp(&days(b)).then(amount(a).total_cmp(&amount(b)))),
+        None => rates.iter().min_by(|a, b| amount(a).total_cmp(&amount(b))),
+    };
+    chosen.ok_or_else(|| match rate_id {
+        Some(id) => CliError::InvalidInput(format!(
+            "Rate {id} is not in the saved rates. Re-run `arky shipping rates ... --save`"
+        )),
+        None => CliError::InvalidInput("The saved rates are empty".into()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_rate() {
+        let saved = json!([
+            {"rateId": "r1", "carrier": "usps", "amount": 795, "estimatedDays": 3},
+            {"rateId": "r2", "carrier": "ups", "amount": 1500, "estimatedDays": 1},
+            {"rateId": "r3", "carrier": "fedex", "amount": 650, "estimatedDays": 5},
+        ]);
+        assert_eq!(select_rate(&saved, Some("r2"), false).unwrap()["carrier"], "ups");
+        assert_eq!(select_rate(&saved, None, false).unwrap()["rateId"], "r3");
+        assert_eq!(select_rate(&saved, None, true).unwrap()["rateId"], "r2");
+        assert!(select_rate(&saved, Some("nope"), false).is_err());
+        assert!(select_rate(&json!({"data": []}), None, false).is_err());
+    }
+}
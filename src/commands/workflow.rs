@@ -1,9 +1,28 @@
 use crate::client::ArkyClient;
-use crate::commands::{merge_data, parse_data};
-use crate::error::Result;
+use crate::commands::{
+    created_id, delete_entity, fetch_all, merge_data, parse_data, poll_until, print_created,
+    print_example, print_list, write_out, Example, ListArgs,
+};
+use crate::error::{CliError, Result};
 use crate::output::Format;
 use clap::Subcommand;
-use serde_json::json;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Execution statuses `workflow run-once` stops waiting on
+const FINISHED_EXECUTION_STATUSES: &[&str] = &["completed", "failed"];
+
+/// Server-managed fields dropped by `workflow export`
+const EXPORT_STRIPPED_FIELDS: &[&str] = &[
+    "id",
+    "businessId",
+    "triggerSecret",
+    "createdAt",
+    "updatedAt",
+    "lastExecution",
+    "lastExecutedAt",
+    "executionCount",
+];
 
 #[derive(Subcommand, Debug)]
 pub enum WorkflowCommand {
@@ -29,10 +48,8 @@ pub enum WorkflowCommand {
     List {
         #[arg(long)]
         query: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
         #[arg(long, help = "Comma-separated: draft,active,archived")]
         statuses: Option<String>,
     },
@@ -131,9 +148,14 @@ pub enum WorkflowCommand {
         }'")]
     Create {
         /// Workflow key (unique within business)
+        #[arg(required_unless_present = "example", default_value = "")]
         key: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Print only the created ID, e.g. for ID=$(arky ... --id-only)")]
+        id_only: bool,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Update a workflow
     #[command(long_about = "Update a workflow by ID.\n\n\
@@ -145,30 +167,92 @@ pub enum WorkflowCommand {
         arky workflow update WF_ID --data '{\"nodes\": {...}, \"status\": \"active\"}'")]
     Update {
         /// Workflow ID
+        #[arg(required_unless_present = "example", default_value = "")]
         id: String,
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
+        #[arg(long, help = "Print a --data skeleton to edit and exit")]
+        example: bool,
     },
     /// Delete a workflow
     Delete {
         /// Workflow ID
         id: String,
+        #[arg(long, help = "Treat an already-deleted ID (404) as success")]
+        ignore_missing: bool,
+    },
+    /// Export a workflow definition for version control
+    #[command(long_about = "Export a workflow as a portable JSON definition.\n\n\
+        Server-managed fields (id, businessId, triggerSecret, timestamps and\n\
+        execution state) are stripped so the file can be committed and imported\n\
+        into any business with `arky workflow import`.\n\n\
+        Examples:\n\
+        arky workflow export WF_ID\n\
+        arky workflow export WF_ID --out workflows/welcome.json")]
+    Export {
+        /// Workflow ID
+        id: String,
+        #[arg(long, help = "Output file path, or - for stdout (default)")]
+        out: Option<String>,
     },
-    /// Trigger a workflow by its secret
+    /// Create or update a workflow from an exported definition
+    #[command(long_about = "Import a workflow definition (as written by `arky workflow export`).\n\n\
+        The workflow is matched by key: if one exists it is updated, otherwise\n\
+        it is created. The node graph is validated locally first:\n\
+          - exactly one trigger node\n\
+          - every edge points at an existing node\n\
+          - switch conditions reference `input`\n\n\
+        Examples:\n\
+        arky workflow import workflows/welcome.json\n\
+        cat welcome.json | arky workflow import -")]
+    Import {
+        /// Definition file, or - for stdin
+        file: String,
+    },
+    /// Trigger a workflow by its secret or ID
     #[command(long_about = "Trigger a workflow execution via its trigger secret.\n\n\
         The trigger secret is returned when creating a workflow.\n\
-        You can find it via `arky workflow get WORKFLOW_ID` in the triggerSecret field.\n\n\
+        You can find it via `arky workflow get WORKFLOW_ID` in the triggerSecret field,\n\
+        or pass --id WORKFLOW_ID instead of the secret to have it looked up.\n\n\
         Pass input data via --data to make it available as `trigger` in expressions.\n\n\
         Examples:\n\
         arky workflow trigger sec_abc123\n\
+        arky workflow trigger --id WORKFLOW_ID\n\
         arky workflow trigger sec_abc123 --data '{\"email\": \"user@example.com\", \"type\": \"welcome\"}'")]
     Trigger {
         /// Workflow trigger secret
-        secret: String,
+        #[arg(required_unless_present = "id")]
+        secret: Option<String>,
+        #[arg(long, conflicts_with = "secret", help = "Workflow ID; its triggerSecret is fetched")]
+        id: Option<String>,
         /// JSON payload to pass as trigger input
         #[arg(long, help = "JSON data: inline, @file, or - for stdin")]
         data: Option<String>,
     },
+    /// Run a throwaway workflow once and print the execution
+    #[command(name = "run-once", long_about = "Run a throwaway workflow once.\n\n\
+        Creates a temporary draft workflow, triggers it, waits for the execution\n\
+        to finish, prints it, and deletes the workflow again.\n\n\
+        --data is the node map (or a full definition with a \"nodes\" field); it is\n\
+        validated like `workflow import`. --input is the trigger payload.\n\
+        The workflow is deleted even when the run fails or times out; if that\n\
+        delete fails, its ID is printed so it can be removed by hand.\n\n\
+        Example:\n\
+        arky workflow run-once --input '{\"n\": 2}' --data '{\n\
+          \"trigger\": {\"type\": \"trigger\"},\n\
+          \"double\": {\"type\": \"transform\", \"code\": \"trigger.n * 2\",\n\
+                     \"edges\": [{\"node\": \"trigger\", \"output\": \"default\"}]}\n\
+        }'")]
+    RunOnce {
+        #[arg(long, help = "Node map or workflow definition: inline, @file, or - for stdin")]
+        data: Option<String>,
+        #[arg(long, help = "Trigger payload JSON: inline, @file, or - for stdin")]
+        input: Option<String>,
+        #[arg(long, default_value_t = 2, help = "Seconds between execution status checks")]
+        interval: u64,
+        #[arg(long, default_value_t = 120, help = "Seconds to wait for the execution")]
+        wait_timeout: u64,
+    },
     /// List executions of a workflow
     #[command(long_about = "List past executions of a workflow.\n\n\
         Statuses: pending, running, completed, failed.\n\n\
@@ -177,10 +261,8 @@ pub enum WorkflowCommand {
     Executions {
         /// Workflow ID
         workflow_id: String,
-        #[arg(long, default_value = "20")]
-        limit: u32,
-        #[arg(long)]
-        cursor: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
         #[arg(long, help = "Filter: pending, running, completed, failed")]
         status: Option<String>,
     },
@@ -198,6 +280,9 @@ pub enum WorkflowCommand {
 }
 
 pub async fn handle(cmd: WorkflowCommand, client: &ArkyClient, format: &Format) -> Result<()> {
+    if cmd.is_example() {
+        return print_example(&example());
+    }
     let biz_id = client.require_business_id()?;
 
     match cmd {
@@ -205,84 +290,193 @@ pub async fn handle(cmd: WorkflowCommand, client: &ArkyClient, format: &Format)
             let result = client
                 .get(&format!("/v1/businesses/{biz_id}/workflows/{id}"), &[])
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
         WorkflowCommand::List {
             query,
-            limit,
-            cursor,
+            list,
             statuses,
         } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref q) = query {
                 params.push(("query", q.clone()));
             }
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
             if let Some(ref s) = statuses {
                 params.push(("statuses", s.clone()));
             }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(&format!("/v1/businesses/{biz_id}/workflows"), &params_ref)
-                .await?;
-            crate::output::print_output(&result, format);
+            print_list(
+                client,
+                &format!("/v1/businesses/{biz_id}/workflows"),
+                &params_ref,
+                &list,
+                format,
+            )
+            .await?;
         }
-        WorkflowCommand::Create { key, data } => {
+        WorkflowCommand::Create {
+            key, data, id_only, ..
+        } => {
             let mut body = json!({ "key": key, "businessId": biz_id });
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
+            merge_data(&mut body, overlay)?;
             let result = client
                 .post(&format!("/v1/businesses/{biz_id}/workflows"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            print_created(&result, "workflow", id_only, format)?;
         }
-        WorkflowCommand::Update { id, data } => {
+        WorkflowCommand::Update { id, data, .. } => {
             let mut body = json!({ "id": id });
             let overlay = parse_data(data.as_deref())?;
-            merge_data(&mut body, overlay);
+            merge_data(&mut body, overlay)?;
             let result = client
                 .put(&format!("/v1/businesses/{biz_id}/workflows/{id}"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
+        }
+        WorkflowCommand::Delete { id, ignore_missing } => {
+            let path = format!("/v1/businesses/{biz_id}/workflows/{id}");
+            if delete_entity(client, &path, "workflow", ignore_missing).await? {
+                crate::output::print_success("Workflow deleted");
+            }
         }
-        WorkflowCommand::Delete { id } => {
-            let _ = client
-                .delete(&format!("/v1/businesses/{biz_id}/workflows/{id}"))
+        WorkflowCommand::Export { id, out } => {
+            let mut workflow = client
+                .get(&format!("/v1/businesses/{biz_id}/workflows/{id}"), &[])
                 .await?;
-            crate::output::print_success("Workflow deleted");
+            if let Some(obj) = workflow.as_object_mut() {
+                for field in EXPORT_STRIPPED_FIELDS {
+                    obj.remove(*field);
+                }
+            }
+            let out = out.unwrap_or_else(|| "-".to_string());
+            let content = serde_json::to_string_pretty(&workflow)? + "\n";
+            write_out(&out, content.as_bytes())?;
+            if out != "-" {
+                crate::output::print_success(&format!("Workflow exported to {out}"));
+            }
+        }
+        WorkflowCommand::Import { file } => {
+            let source = if file == "-" { file } else { format!("@{file}") };
+            let mut definition = parse_data(Some(&source))?;
+            let key = definition["key"]
+                .as_str()
+                .ok_or_else(|| CliError::InvalidInput("Workflow definition has no key".into()))?
+                .to_string();
+            validate_nodes(&definition["nodes"])?;
+
+            let path = format!("/v1/businesses/{biz_id}/workflows");
+            let existing = fetch_all(client, &path, &[("query", &key)]).await?;
+            let existing_id = existing
+                .iter()
+                .find(|w| w["key"] == key.as_str())
+                .and_then(|w| w["id"].as_str());
+
+            let result = match existing_id {
+                Some(id) => {
+                    definition["id"] = json!(id);
+                    client.put(&format!("{path}/{id}"), &definition).await?
+                }
+                None => {
+                    definition["businessId"] = json!(biz_id);
+                    client.post(&path, &definition).await?
+                }
+            };
+            crate::output::print_output(&result, format)?;
         }
-        WorkflowCommand::Trigger { secret, data } => {
+        WorkflowCommand::Trigger { secret, id, data } => {
             let body = parse_data(data.as_deref())?;
+            let secret = match (secret, id) {
+                (Some(secret), _) => secret,
+                (None, id) => {
+                    let id = id.ok_or_else(|| {
+                        CliError::InvalidInput("Pass a trigger SECRET or --id WORKFLOW_ID".into())
+                    })?;
+                    let workflow = client
+                        .get(&format!("/v1/businesses/{biz_id}/workflows/{id}"), &[])
+                        .await?;
+                    trigger_secret(&workflow)
+                        .ok_or_else(|| {
+                            CliError::InvalidInput(format!("Workflow {id} has no triggerSecret"))
+                        })?
+                        .to_string()
+                }
+            };
             let result = client
                 .post(&format!("/v1/workflows/trigger/{secret}"), &body)
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
+        }
+        WorkflowCommand::RunOnce {
+            data,
+            input,
+            interval,
+            wait_timeout,
+        } => {
+            let definition = parse_data(data.as_deref())?;
+            let nodes = match definition.get("nodes") {
+                Some(nodes) => nodes.clone(),
+                None => definition,
+            };
+            validate_nodes(&nodes)?;
+            let input = parse_data(input.as_deref())?;
+
+            let path = format!("/v1/businesses/{biz_id}/workflows");
+            let key = format!("run-once-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+            let body = json!({
+                "key": key,
+                "businessId": biz_id,
+                "status": "draft",
+                "nodes": nodes,
+            });
+            let created = client.post(&path, &body).await?;
+            let id = created_id(&created)
+                .ok_or_else(|| CliError::InvalidInput("Create response has no id".into()))?
+                .to_string();
+
+            let outcome = run_once(
+                client,
+                &format!("{path}/{id}"),
+                &created,
+                &input,
+                Duration::from_secs(interval),
+                Duration::from_secs(wait_timeout),
+            )
+            .await;
+            // Best-effort cleanup, whatever happened above
+            if let Err(e) = client.delete(&format!("{path}/{id}")).await {
+                crate::output::print_warning(&format!(
+                    "Could not delete temporary workflow {id}: {e}. \
+                     Remove it with `arky workflow delete {id}`"
+                ));
+            }
+
+            let execution = outcome?;
+            crate::output::print_output(&execution, format)?;
+            if execution["status"] == "failed" {
+                return Err(CliError::InvalidInput("Workflow execution failed".into()));
+            }
         }
         WorkflowCommand::Executions {
             workflow_id,
-            limit,
-            cursor,
+            list,
             status,
         } => {
-            let mut params: Vec<(&str, String)> = vec![("limit", limit.to_string())];
-            if let Some(ref c) = cursor {
-                params.push(("cursor", c.clone()));
-            }
+            let mut params: Vec<(&str, String)> = vec![];
             if let Some(ref s) = status {
                 params.push(("status", s.clone()));
             }
             let params_ref: Vec<(&str, &str)> =
                 params.iter().map(|(k, v)| (*k, v.as_str())).collect();
-            let result = client
-                .get(
-                    &format!("/v1/businesses/{biz_id}/workflows/{workflow_id}/executions"),
-                    &params_ref,
-                )
-                .await?;
-            crate::output::print_output(&result, format);
+            print_list(
+                client,
+                &format!("/v1/businesses/{biz_id}/workflows/{workflow_id}/executions"),
+                &params_ref,
+                &list,
+                format,
+            )
+            .await?;
         }
         WorkflowCommand::Execution {
             workflow_id,
@@ -296,8 +490,214 @@ pub async fn handle(cmd: WorkflowCommand, client: &ArkyClient, format: &Format)
                     &[],
                 )
                 .await?;
-            crate::output::print_output(&result, format);
+            crate::output::print_output(&result, format)?;
         }
     }
     Ok(())
 }
+
+fn example() -> Value {
+    json!({
+        "status": "draft",
+        "nodes": {
+            "trigger": { "type": "trigger" },
+            "process": {
+                "type": "transform",
+                "code": "trigger",
+                "edges": [{ "node": "trigger", "output": "default" }]
+            }
+        }
+    })
+}
+
+/// Trigger the workflow created at `path` and wait for its execution to finish
+async fn run_once(
+    client: &ArkyClient,
+    path: &str,
+    created: &Value,
+    input: &Value,
+    interval: Duration,
+    timeout: Duration,
+) -> Result<Value> {
+    let secret = match trigger_secret(created) {
+        Some(secret) => secret.to_string(),
+        None => trigger_secret(&client.get(path, &[]).await?)
+            .ok_or_else(|| CliError::InvalidInput("Workflow has no triggerSecret".into()))?
+            .to_string(),
+    };
+    let triggered = client
+        .post(&format!("/v1/workflows/trigger/{secret}"), input)
+        .await?;
+    let executions = format!("{path}/executions");
+    let execution_id = triggered["executionId"]
+        .as_str()
+        .or_else(|| triggered["data"]["executionId"].as_str())
+        .map(str::to_string);
+
+    poll_until(
+        "the workflow execution",
+        || async {
+            match execution_id {
+                Some(ref eid) => client.get(&format!("{executions}/{eid}"), &[]).await,
+                // No ID from the trigger: the newest execution is ours
+                None => {
+                    let page = client.get(&executions, &[("limit", "1")]).await?;
+                    Ok(page["data"].get(0).cloned().unwrap_or(Value::Null))
+                }
+            }
+        },
+        |execution| {
+            execution["status"]
+                .as_str()
+                .is_some_and(|s| FINISHED_EXECUTION_STATUSES.contains(&s))
+        },
+        interval,
+        timeout,
+    )
+    .await
+}
+
+fn trigger_secret(workflow: &Value) -> Option<&str> {
+    workflow["triggerSecret"]
+        .as_str()
+        .or_else(|| workflow["data"]["triggerSecret"].as_str())
+        .filter(|s| !s.is_empty())
+}
+
+/// Local sanity check of a workflow node graph before sending it
+fn validate_nodes(nodes: &Value) -> Result<()> {
+    let nodes = nodes
+        .as_object()
+        .ok_or_else(|| CliError::InvalidInput("Workflow definition has no nodes object".into()))?;
+
+    let triggers = nodes.values().filter(|n| n["type"] == "trigger").count();
+    if triggers != 1 {
+        return Err(CliError::InvalidInput(format!(
+            "Workflow must have exactly one trigger node, found {triggers}"
+        )));
+    }
+
+    for (name, node) in nodes {
+        for edge in node["edges"].as_array().into_iter().flatten() {
+            let source = edge["node"].as_str().unwrap_or_default();
+            if !nodes.contains_key(source) {
+                return Err(CliError::InvalidInput(format!(
+                    "Node '{name}' has an edge from unknown node '{source}'"
+                )));
+            }
+        }
+        if node["type"] == "switch" {
+            for rule in node["rules"].as_array().into_iter().flatten() {
+                let condition = rule["condition"].as_str().unwrap_or_default();
+                if !condition.contains("input") {
+                    return Err(CliError::InvalidInput(format!(
+                        "Switch node '{name}' condition must reference `input`: {condition}"
+                    )));
+                }
+            }
+        }
+    }
+
+    let cycle = nodes_in_cycles(nodes);
+    if !cycle.is_empty() {
+        return Err(CliError::InvalidInput(format!(
+            "Workflow edges form a cycle through: {}",
+            cycle.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Nodes on or between cycles, empty for a DAG. Kahn's sort peels off nodes
+/// with no unvisited inputs, then the same from the other end drops nodes
+/// that only hang off a cycle, so the error names just the loop.
+fn nodes_in_cycles(nodes: &serde_json::Map<String, Value>) -> Vec<String> {
+    use std::collections::{BTreeMap, BTreeSet};
+    // (source, target) pairs; each edge on a node names the node feeding it
+    let edges: Vec<(&str, &str)> = nodes
+        .iter()
+        .flat_map(|(name, node)| {
+            node["edges"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|e| e["node"].as_str())
+                .map(move |source| (source, name.as_str()))
+        })
+        .collect();
+    let mut left: BTreeSet<&str> = nodes.keys().map(String::as_str).collect();
+    for reverse in [false, true] {
+        loop {
+            let mut degree: BTreeMap<&str, usize> = left.iter().map(|n| (*n, 0)).collect();
+            for &(source, target) in &edges {
+                let (from, to) = if reverse { (target, source) } else { (source, target) };
+                if left.contains(from) {
+                    if let Some(d) = degree.get_mut(to) {
+                        *d += 1;
+                    }
+                }
+            }
+            let free: Vec<&str> =
+                degree.into_iter().filter(|(_, d)| *d == 0).map(|(n, _)| n).collect();
+            if free.is_empty() {
+                break;
+            }
+            for n in free {
+                left.remove(n);
+            }
+        }
+    }
+    left.into_iter().map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigger_secret() {
+        assert_eq!(trigger_secret(&json!({"triggerSecret": "sec_1"})), Some("sec_1"));
+        assert_eq!(trigger_secret(&json!({"data": {"triggerSecret": "sec_2"}})), Some("sec_2"));
+        assert_eq!(trigger_secret(&json!({"triggerSecret": ""})), None);
+    }
+
+    #[test]
+    fn test_validate_nodes_ok() {
+        let nodes = json!({
+            "trigger": {"type": "trigger"},
+            "check": {"type": "switch", "rules": [{"condition": "input.x > 1"}],
+                      "edges": [{"node": "trigger", "output": "default"}]}
+        });
+        assert!(validate_nodes(&nodes).is_ok());
+    }
+
+    #[test]
+    fn test_validate_nodes_errors() {
+        assert!(validate_nodes(&json!({"a": {"type": "transform"}})).is_err());
+        let unknown_edge = json!({
+            "trigger": {"type": "trigger"},
+            "b": {"type": "transform", "edges": [{"node": "missing", "output": "default"}]}
+        });
+        assert!(validate_nodes(&unknown_edge).is_err());
+        let bad_switch = json!({
+            "trigger": {"type": "trigger"},
+            "s": {"type": "switch", "rules": [{"condition": "true"}]}
+        });
+        assert!(validate_nodes(&bad_switch).is_err());
+    }
+
+    #[test]
+    fn test_validate_nodes_cycle() {
+        let cyclic = json!({
+            "trigger": {"type": "trigger"},
+            "a": {"type": "transform", "edges": [
+                {"node": "trigger", "output": "default"}, {"node": "c", "output": "default"}
+            ]},
+            "b": {"type": "transform", "edges": [{"node": "a", "output": "default"}]},
+            "c": {"type": "transform", "edges": [{"node": "b", "output": "default"}]},
+            "after": {"type": "transform", "edges": [{"node": "c", "output": "default"}]}
+        });
+        let err = validate_nodes(&cyclic).unwrap_err().to_string();
+        assert!(err.contains("cycle through: a, b, c"), "{err}");
+    }
+}
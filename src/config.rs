@@ -1,6 +1,37 @@
 use crate::error::{CliError, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use serde_json::{Map, Value};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// --profile: ~/.arky/profiles/<name>.json, layered over config.json
+static PROFILE: OnceLock<String> = OnceLock::new();
+/// --config: explicit config file, taking precedence over --profile
+static CONFIG_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Server used when no base_url is configured (the local dev server's port)
+pub const DEFAULT_BASE_URL: &str = "http://localhost:8000";
+
+/// Keys of the current config schema
+const CONFIG_KEYS: &[&str] = &[
+    "base_url",
+    "business_id",
+    "business_key",
+    "token",
+    "format",
+    "refresh_token",
+    "account_id",
+];
+
+/// Older camelCase spellings and the keys they map to
+const LEGACY_KEYS: &[(&str, &str)] = &[
+    ("baseUrl", "base_url"),
+    ("businessId", "business_id"),
+    ("businessKey", "business_key"),
+    ("refreshToken", "refresh_token"),
+    ("accountId", "account_id"),
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
@@ -8,10 +39,17 @@ pub struct Config {
     pub base_url: Option<String>,
     #[serde(default)]
     pub business_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub business_key: Option<String>,
     #[serde(default)]
     pub token: Option<String>,
     #[serde(default)]
     pub format: Option<String>,
+    /// Saved by `auth verify`/`auth session` for token refresh
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
 }
 
 impl Config {
@@ -21,91 +59,464 @@ impl Config {
             .join(".arky")
     }
 
+    /// The active config file, which commands like `config set` write to: the
+    /// --config file, else the --profile file when one is selected
     pub fn config_path() -> PathBuf {
-        Self::config_dir().join("config.json")
+        match active_profile() {
+            Some(name) => Self::profile_path(name),
+            None => Self::base_path(),
+        }
+    }
+
+    /// The config file under any profile: the --config file, else
+    /// ~/.arky/config.json
+    fn base_path() -> PathBuf {
+        match CONFIG_FILE.get() {
+            Some(path) => path.clone(),
+            None => Self::config_dir().join("config.json"),
+        }
+    }
+
+    pub fn profile_path(name: &str) -> PathBuf {
+        Self::config_dir().join("profiles").join(format!("{name}.json"))
     }
 
+    /// A named profile, or (for a new one) the active config's connection
+    /// settings without its credentials
+    pub fn load_profile(name: &str) -> Result<Config> {
+        if let Some(raw) = Self::load_raw_from(&Self::profile_path(name))? {
+            return Ok(Self::migrate(raw).0);
+        }
+        Ok(Config {
+            token: None,
+            refresh_token: None,
+            account_id: None,
+            ..Self::load_file()
+        })
+    }
+
+    pub fn save_profile(&self, name: &str) -> Result<()> {
+        self.save_to(&Self::profile_path(name))
+    }
+
+    /// Load the config file, upgrading older schemas in memory (see `migrate`).
+    /// The file itself is only rewritten on save or by `arky config migrate`.
     pub fn load_file() -> Config {
-        let path = Self::config_path();
-        if path.exists() {
-            let content = std::fs::read_to_string(&path).unwrap_or_default();
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            Config::default()
+        Self::load_from(&Self::config_path())
+    }
+
+    fn load_from(path: &Path) -> Config {
+        match Self::load_raw_from(path) {
+            Ok(Some(raw)) => Self::migrate(raw).0,
+            _ => Config::default(),
+        }
+    }
+
+    /// The config file as plain JSON, or None when it doesn't exist
+    pub fn load_raw() -> Result<Option<Value>> {
+        Self::load_raw_from(&Self::config_path())
+    }
+
+    fn load_raw_from(path: &Path) -> Result<Option<Value>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map(Some).map_err(|e| {
+            CliError::Config(format!("Cannot parse {}: {e}", path.display()))
+        })
+    }
+
+    /// Upgrade a raw config to the current schema: legacy camelCase keys are
+    /// renamed, unknown keys and non-string values dropped. Returns the config
+    /// and a description of each change (empty when already current).
+    pub fn migrate(raw: Value) -> (Config, Vec<String>) {
+        let Value::Object(obj) = raw else {
+            return (Config::default(), vec!["replaced non-object config".into()]);
+        };
+        let mut changes = vec![];
+        let mut current = Map::new();
+        for (key, value) in &obj {
+            let name = match LEGACY_KEYS.iter().find(|(old, _)| old == key) {
+                // The current spelling wins when both are present
+                Some((_, new)) if obj.contains_key(*new) => {
+                    changes.push(format!("dropped '{key}' (superseded by '{new}')"));
+                    continue;
+                }
+                Some((_, new)) => {
+                    changes.push(format!("renamed '{key}' to '{new}'"));
+                    new.to_string()
+                }
+                None if CONFIG_KEYS.contains(&key.as_str()) => key.clone(),
+                None => {
+                    changes.push(format!("dropped unknown key '{key}'"));
+                    continue;
+                }
+            };
+            if !(value.is_string() || value.is_null()) {
+                changes.push(format!("dropped '{name}' (expected a string, got {value})"));
+                continue;
+            }
+            current.insert(name, value.clone());
         }
+        let cfg = serde_json::from_value(Value::Object(current)).unwrap_or_default();
+        (cfg, changes)
+    }
+
+    /// The same config without credentials, for sharing
+    pub fn without_secrets(&self) -> Config {
+        Config {
+            token: None,
+            refresh_token: None,
+            ..self.clone()
+        }
+    }
+
+    pub fn has_secrets(&self) -> bool {
+        self.token.is_some() || self.refresh_token.is_some()
+    }
+
+    /// Copy every value set in `other` over this config, keeping the rest.
+    /// Returns the keys that were set.
+    pub fn merge(&mut self, other: Config) -> Vec<&'static str> {
+        let mut set = vec![];
+        let mut take = |key, ours: &mut Option<String>, theirs: Option<String>| {
+            if theirs.is_some() {
+                *ours = theirs;
+                set.push(key);
+            }
+        };
+        take("base_url", &mut self.base_url, other.base_url);
+        take("business_id", &mut self.business_id, other.business_id);
+        take("business_key", &mut self.business_key, other.business_key);
+        take("token", &mut self.token, other.token);
+        take("format", &mut self.format, other.format);
+        take("refresh_token", &mut self.refresh_token, other.refresh_token);
+        take("account_id", &mut self.account_id, other.account_id);
+        set
+    }
+
+    /// The values `resolve` reads, in `SETTINGS` order
+    fn settings(self) -> [Option<String>; 5] {
+        [self.base_url, self.business_id, self.business_key, self.token, self.format]
     }
 
     pub fn save_file(&self) -> Result<()> {
-        let dir = Self::config_dir();
-        std::fs::create_dir_all(&dir)?;
+        self.save_to(&Self::config_path())
+    }
+
+    /// Write the config and restrict it to the current user (file 0600, dir 0700).
+    /// On Windows the file inherits the profile directory's ACLs.
+    fn save_to(&self, path: &Path) -> Result<()> {
+        let dir = path
+            .parent()
+            .filter(|d| !d.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        // A --config file may live in a project directory; only lock down
+        // directories we own (~/.arky) or create
+        let own_dir = !dir.exists() || dir.starts_with(Self::config_dir());
+        std::fs::create_dir_all(dir)?;
         let content = serde_json::to_string_pretty(self)?;
-        std::fs::write(Self::config_path(), content)?;
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // Created 0600 so the token is never readable by others, even briefly
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let private_dir = std::fs::Permissions::from_mode(0o700);
+            if own_dir {
+                std::fs::set_permissions(dir, private_dir.clone())?;
+            }
+            // Profiles live under ~/.arky/profiles; lock down ~/.arky as well
+            let config_dir = Self::config_dir();
+            if dir.starts_with(&config_dir) && dir != config_dir {
+                std::fs::set_permissions(&config_dir, private_dir)?;
+            }
+            // An existing file keeps its old mode through open, so reset it
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        }
+        std::io::Write::write_all(&mut file, content.as_bytes())?;
         Ok(())
     }
 
-    /// Resolve config with priority: CLI flags > env vars > config file
+    /// Resolve config with priority: CLI flags > env vars (incl. --env-file) >
+    /// --profile file > config file (--config, else ~/.arky/config.json) >
+    /// default. Each key falls through on its own, so a profile only needs the
+    /// keys it changes.
+    /// Blank values at any level count as not provided, so `--business-id ""`
+    /// falls through to the env/config value instead of clearing it.
     pub fn resolve(
         flag_base_url: Option<&str>,
         flag_business_id: Option<&str>,
+        flag_business_key: Option<&str>,
         flag_token: Option<&str>,
         flag_format: Option<&str>,
     ) -> ResolvedConfig {
-        let file = Self::load_file();
-
-        let base_url = flag_base_url
-            .map(|s| s.to_string())
-            .or_else(|| std::env::var("ARKY_BASE_URL").ok())
-            .or(file.base_url)
-            .unwrap_or_else(|| "http://localhost:3000".to_string());
-
-        let business_id = flag_business_id
-            .map(|s| s.to_string())
-            .or_else(|| std::env::var("ARKY_BUSINESS_ID").ok())
-            .or(file.business_id);
-
-        let token = flag_token
-            .map(|s| s.to_string())
-            .or_else(|| std::env::var("ARKY_TOKEN").ok())
-            .or(file.token);
-
-        let format = flag_format
-            .map(|s| s.to_string())
-            .or_else(|| std::env::var("ARKY_FORMAT").ok())
-            .or(file.format)
-            .unwrap_or_else(|| "json".to_string());
+        let flags = [flag_base_url, flag_business_id, flag_business_key, flag_token, flag_format];
+        let profile = active_profile().map_or_else(Config::default, |name| {
+            Self::load_from(&Self::profile_path(name))
+        });
+        let file = Self::load_from(&Self::base_path());
+        Self::resolve_from(flags, |var| std::env::var(var).ok(), profile, file)
+    }
 
+    /// `resolve` over explicit inputs: flags in `SETTINGS` order, an env lookup,
+    /// the loaded profile (default when none is active) and config file
+    fn resolve_from(
+        flags: [Option<&str>; 5],
+        env: impl Fn(&str) -> Option<String>,
+        profile: Config,
+        file: Config,
+    ) -> ResolvedConfig {
+        let mut profiles = profile.settings();
+        let mut files = file.settings();
+        let picked: [(Option<String>, Source); 5] = std::array::from_fn(|i| {
+            let setting = &SETTINGS[i];
+            let from_env = env(setting.env);
+            // clap fills the flags from the same variables, so a flag equal to
+            // its variable came from the environment
+            let flag = flags[i].filter(|f| from_env.as_deref() != Some(*f));
+            pick([
+                (Source::Flag, flag.map(String::from)),
+                (Source::Env, from_env),
+                (Source::Profile, profiles[i].take()),
+                (Source::File, files[i].take()),
+                (Source::Default, setting.default.map(String::from)),
+            ])
+        });
+        let sources = SETTINGS.iter().zip(&picked).map(|(s, (_, source))| (s.key, *source));
+        let sources = sources.collect();
+        let [base_url, business_id, business_key, token, format] = picked.map(|(value, _)| value);
         ResolvedConfig {
-            base_url,
+            base_url: base_url.unwrap_or_default(),
             business_id,
+            // Only consulted when no business_id is set; resolved to an id by the client
+            business_key,
             token,
-            format,
+            format: format.unwrap_or_default(),
+            sources,
+        }
+    }
+}
+
+/// A resolvable setting with its flag, environment variable and default
+pub struct Setting {
+    pub key: &'static str,
+    pub flag: &'static str,
+    pub env: &'static str,
+    pub default: Option<&'static str>,
+}
+
+/// Settings `Config::resolve` fills in, in `ResolvedConfig` field order
+pub const SETTINGS: [Setting; 5] = [
+    Setting {
+        key: "base_url",
+        flag: "--base-url",
+        env: "ARKY_BASE_URL",
+        default: Some(DEFAULT_BASE_URL),
+    },
+    Setting {
+        key: "business_id",
+        flag: "--business-id",
+        env: "ARKY_BUSINESS_ID",
+        default: None,
+    },
+    Setting {
+        key: "business_key",
+        flag: "--business-key",
+        env: "ARKY_BUSINESS_KEY",
+        default: None,
+    },
+    Setting {
+        key: "token",
+        flag: "--token",
+        env: "ARKY_TOKEN",
+        default: None,
+    },
+    Setting {
+        key: "format",
+        flag: "--format",
+        env: "ARKY_FORMAT",
+        default: Some("json"),
+    },
+];
+
+impl Setting {
+    /// Where this setting's value came from, e.g. "env ARKY_TOKEN"
+    pub fn describe(&self, source: Source) -> String {
+        match source {
+            Source::Flag => format!("flag {}", self.flag),
+            Source::Env => format!("env {}", self.env),
+            Source::Profile => match active_profile() {
+                Some(name) => format!("profile {name} ({})", Config::config_path().display()),
+                None => "profile".to_string(),
+            },
+            Source::File => format!("config file {}", Config::base_path().display()),
+            Source::Default => "default".to_string(),
+            Source::Unset => "not set".to_string(),
+        }
+    }
+}
+
+/// Where a resolved setting came from (`arky config show --explain`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Flag,
+    Env,
+    Profile,
+    File,
+    Default,
+    Unset,
+}
+
+/// The first non-blank value in precedence order, with its source
+fn pick(layers: [(Source, Option<String>); 5]) -> (Option<String>, Source) {
+    layers
+        .into_iter()
+        .find_map(|(source, value)| {
+            value.filter(|v| !v.trim().is_empty()).map(|v| (Some(v), source))
+        })
+        .unwrap_or((None, Source::Unset))
+}
+
+/// The --profile name, unless --config names a file to use instead
+fn active_profile() -> Option<&'static String> {
+    PROFILE.get().filter(|_| CONFIG_FILE.get().is_none())
+}
+
+/// Use `path` as the config file for this run (--config / ARKY_CONFIG)
+pub fn set_config_file(path: Option<String>) {
+    if let Some(path) = path {
+        let _ = CONFIG_FILE.set(PathBuf::from(path));
+    }
+}
+
+/// Select the profile for this run. Names are kept to letters, digits, `-`
+/// and `_` so they map to a plain file name.
+pub fn set_profile(name: Option<String>) -> Result<()> {
+    let Some(name) = name else {
+        return Ok(());
+    };
+    validate_profile_name(&name)?;
+    let _ = PROFILE.set(name);
+    Ok(())
+}
+
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(CliError::Config(format!(
+            "Invalid profile name '{name}'. Use letters, digits, - and _"
+        )));
+    }
+    Ok(())
+}
+
+/// Load `ARKY_*` variables from a dotenv file before flags are parsed.
+/// An explicit `--env-file` must exist; otherwise `./.env` is used when present.
+/// Variables already set in the environment win over the file.
+/// Returns whether a file was loaded.
+pub fn load_env_file(explicit: Option<&str>) -> Result<bool> {
+    let path = match explicit {
+        Some(p) => Path::new(p),
+        None if Path::new(".env").is_file() => Path::new(".env"),
+        None => return Ok(false),
+    };
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| CliError::Config(format!("Cannot read env file {}: {e}", path.display())))?;
+    for (key, value) in parse_dotenv(&content) {
+        if key.starts_with("ARKY_") && std::env::var_os(&key).is_none() {
+            std::env::set_var(key, value);
         }
     }
+    Ok(true)
+}
+
+/// `--token -` (or `--token-stdin`): read the token instead of taking it from
+/// argv. A terminal gets a prompt without echo; piped stdin gives its first
+/// line, leaving the rest for `--data -`.
+pub fn read_token_from_stdin() -> Result<String> {
+    let token = if std::io::stdin().is_terminal() {
+        rpassword::prompt_password("Token: ")?
+    } else {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        line
+    };
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(CliError::Config("No token read from stdin".into()));
+    }
+    Ok(token.to_string())
+}
+
+/// Parse `KEY=value` lines: blank lines and `#` comments are skipped, an
+/// `export ` prefix is allowed, and values may be single- or double-quoted.
+fn parse_dotenv(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = match value.chars().next() {
+                Some(q @ ('"' | '\'')) if value.len() > 1 && value.ends_with(q) => {
+                    &value[1..value.len() - 1]
+                }
+                _ => value.split(" #").next().unwrap_or("").trim_end(),
+            };
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
 }
 
 #[derive(Debug, Clone)]
 pub struct ResolvedConfig {
     pub base_url: String,
     pub business_id: Option<String>,
+    pub business_key: Option<String>,
     pub token: Option<String>,
     pub format: String,
+    /// Where each of the above came from, keyed as in `SETTINGS`
+    pub sources: Vec<(&'static str, Source)>,
 }
 
 impl ResolvedConfig {
+    pub fn source(&self, key: &str) -> Source {
+        self.sources
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map_or(Source::Unset, |(_, source)| *source)
+    }
+
     pub fn require_business_id(&self) -> Result<&str> {
-        self.business_id.as_deref().ok_or_else(|| {
-            CliError::Config(
-                "business_id required. Set via --business-id, ARKY_BUSINESS_ID, or `arky config set business_id <id>`".into(),
-            )
-        })
+        self.business_id
+            .as_deref()
+            .filter(|id| !id.trim().is_empty())
+            .ok_or_else(|| {
+                CliError::Config(
+                    "business_id required. Set via --business-id, ARKY_BUSINESS_ID, --business-key, or `arky config set business_id <id>`".into(),
+                )
+            })
     }
 
     pub fn require_token(&self) -> Result<&str> {
-        self.token.as_deref().ok_or_else(|| {
-            CliError::Config(
-                "token required. Set via --token, ARKY_TOKEN, or `arky auth login`".into(),
-            )
-        })
+        self.token
+            .as_deref()
+            .filter(|t| !t.trim().is_empty())
+            .ok_or_else(|| {
+                CliError::Config(
+                    "token required. Set via --token, ARKY_TOKEN, or `arky auth login`".into(),
+                )
+            })
     }
 }
 
@@ -126,8 +537,10 @@ mod tests {
         let cfg = Config {
             base_url: Some("http://localhost:3000".into()),
             business_id: Some("biz_123".into()),
+            business_key: None,
             token: Some("tok_abc".into()),
             format: Some("json".into()),
+            ..Default::default()
         };
         let json = serde_json::to_string(&cfg).unwrap();
         let parsed: Config = serde_json::from_str(&json).unwrap();
@@ -135,42 +548,257 @@ mod tests {
         assert_eq!(parsed.business_id.unwrap(), "biz_123");
     }
 
+    #[test]
+    fn test_merge_and_without_secrets() {
+        let mut local = Config {
+            base_url: Some("http://localhost:3000".into()),
+            format: Some("table".into()),
+            ..Default::default()
+        };
+        let imported = Config {
+            base_url: Some("https://api.arky.io".into()),
+            business_id: Some("biz_123".into()),
+            token: Some("tok_abc".into()),
+            ..Default::default()
+        };
+        assert!(imported.has_secrets());
+        assert!(!imported.without_secrets().has_secrets());
+        assert_eq!(imported.without_secrets().business_id.as_deref(), Some("biz_123"));
+
+        let set = local.merge(imported);
+        assert_eq!(set, vec!["base_url", "business_id", "token"]);
+        assert_eq!(local.base_url.as_deref(), Some("https://api.arky.io"));
+        assert_eq!(local.format.as_deref(), Some("table"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_restricts_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(".arky").join("config.json");
+        let cfg = Config {
+            token: Some("secret".into()),
+            ..Default::default()
+        };
+        cfg.save_to(&path).unwrap();
+        let mode = |p: &Path| std::fs::metadata(p).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(mode(path.parent().unwrap()), 0o700);
+
+        // A file left world-readable by an older version is tightened on save
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        cfg.save_to(&path).unwrap();
+        assert_eq!(mode(&path), 0o600);
+    }
+
+    #[test]
+    fn test_parse_dotenv() {
+        let parsed = parse_dotenv(
+            "# staging\n\
+             ARKY_BASE_URL=https://staging.example.com\n\
+             export ARKY_TOKEN=\"tok en\"\n\
+             \n\
+             ARKY_FORMAT=table # human output\n\
+             ARKY_BUSINESS_KEY='my-shop'\n\
+             not a pair\n",
+        );
+        let pairs: Vec<(&str, &str)> =
+            parsed.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("ARKY_BASE_URL", "https://staging.example.com"),
+                ("ARKY_TOKEN", "tok en"),
+                ("ARKY_FORMAT", "table"),
+                ("ARKY_BUSINESS_KEY", "my-shop"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_env_file_missing_explicit_path() {
+        assert!(load_env_file(Some("/nonexistent/arky.env")).is_err());
+    }
+
+    #[test]
+    fn test_profile_names() {
+        assert!(validate_profile_name("anon-test_2").is_ok());
+        assert!(validate_profile_name("").is_err());
+        assert!(validate_profile_name("../config").is_err());
+        assert!(Config::profile_path("anon").ends_with("profiles/anon.json"));
+    }
+
     #[test]
     fn test_resolve_defaults() {
         // Clear env vars for test isolation
         std::env::remove_var("ARKY_BASE_URL");
         std::env::remove_var("ARKY_BUSINESS_ID");
+        std::env::remove_var("ARKY_BUSINESS_KEY");
         std::env::remove_var("ARKY_TOKEN");
         std::env::remove_var("ARKY_FORMAT");
 
-        let resolved = Config::resolve(None, None, None, None);
+        let resolved = Config::resolve(None, None, None, None, None);
         assert_eq!(resolved.format, "json");
     }
 
+    #[test]
+    fn test_legacy_config_without_session_fields() {
+        let legacy = r#"{"token": "tok_abc", "format": "json"}"#;
+        let cfg: Config = serde_json::from_str(legacy).unwrap();
+        assert_eq!(cfg.token.as_deref(), Some("tok_abc"));
+        assert!(cfg.refresh_token.is_none() && cfg.account_id.is_none());
+        let json = serde_json::to_string(&cfg).unwrap();
+        assert!(!json.contains("refresh_token") && !json.contains("account_id"));
+    }
+
+    #[test]
+    fn test_migrate_legacy_keys() {
+        let raw = serde_json::json!({
+            "baseUrl": "http://old",
+            "token": "tok_abc",
+            "refreshToken": "ref_1",
+            "businessId": "biz_old",
+            "business_id": "biz_new",
+            "theme": "dark",
+            "format": 3,
+        });
+        let (cfg, changes) = Config::migrate(raw);
+        assert_eq!(cfg.base_url.as_deref(), Some("http://old"));
+        assert_eq!(cfg.refresh_token.as_deref(), Some("ref_1"));
+        assert_eq!(cfg.business_id.as_deref(), Some("biz_new"));
+        assert_eq!(cfg.token.as_deref(), Some("tok_abc"));
+        assert!(cfg.format.is_none());
+        assert_eq!(changes.len(), 5);
+        assert!(changes.contains(&"renamed 'baseUrl' to 'base_url'".to_string()));
+        assert!(changes.contains(&"dropped unknown key 'theme'".to_string()));
+    }
+
+    #[test]
+    fn test_migrate_current_schema_is_unchanged() {
+        let raw = serde_json::json!({"base_url": "http://x", "token": null, "format": "json"});
+        let (_, changes) = Config::migrate(raw);
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_business_key_skipped_on_serialize_when_unset() {
+        let cfg = Config::default();
+        let json = serde_json::to_string(&cfg).unwrap();
+        assert!(!json.contains("business_key"));
+    }
+
     #[test]
     fn test_resolve_flag_priority() {
         std::env::set_var("ARKY_BASE_URL", "http://env-url");
-        let resolved = Config::resolve(Some("http://flag-url"), None, None, None);
+        let resolved = Config::resolve(Some("http://flag-url"), None, None, None, None);
         assert_eq!(resolved.base_url, "http://flag-url");
         std::env::remove_var("ARKY_BASE_URL");
     }
 
+    fn file_config() -> Config {
+        Config {
+            base_url: Some("http://file-url".into()),
+            business_id: Some("biz_file".into()),
+            format: Some("table".into()),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_order() {
+        let env = |var: &str| (var == "ARKY_BUSINESS_ID").then(|| "biz_env".to_string());
+        let flags = [Some("http://flag-url"), None, None, None, None];
+        let resolved = Config::resolve_from(flags, env, Config::default(), file_config());
+        assert_eq!(resolved.base_url, "http://flag-url");
+        assert_eq!(resolved.source("base_url"), Source::Flag);
+        assert_eq!(resolved.business_id.as_deref(), Some("biz_env"));
+        assert_eq!(resolved.source("business_id"), Source::Env);
+        assert_eq!(resolved.format, "table");
+        assert_eq!(resolved.source("format"), Source::File);
+        assert_eq!(resolved.token, None);
+        assert_eq!(resolved.source("token"), Source::Unset);
+
+        let resolved =
+            Config::resolve_from([None; 5], |_| None, Config::default(), Config::default());
+        assert_eq!(resolved.base_url, DEFAULT_BASE_URL);
+        assert_eq!(resolved.format, "json");
+        assert_eq!(resolved.source("base_url"), Source::Default);
+    }
+
+    #[test]
+    fn test_resolve_blank_values_fall_through() {
+        let env = |var: &str| (var == "ARKY_BASE_URL").then(|| " ".to_string());
+        let flags = [None, Some(""), None, None, Some("")];
+        let resolved = Config::resolve_from(flags, env, Config::default(), file_config());
+        assert_eq!(resolved.business_id.as_deref(), Some("biz_file"));
+        assert_eq!(resolved.base_url, "http://file-url");
+        assert_eq!(resolved.format, "table");
+    }
+
+    #[test]
+    fn test_resolve_profile_over_file_per_key() {
+        let profile = Config {
+            token: Some("tok_profile".into()),
+            business_id: Some("biz_profile".into()),
+            ..Config::default()
+        };
+        let env = |var: &str| (var == "ARKY_BUSINESS_ID").then(|| "biz_env".to_string());
+        let resolved = Config::resolve_from([None; 5], env, profile, file_config());
+        assert_eq!(resolved.token.as_deref(), Some("tok_profile"));
+        assert_eq!(resolved.source("token"), Source::Profile);
+        assert_eq!(resolved.business_id.as_deref(), Some("biz_env"));
+        // Not in the profile: falls through to the config file
+        assert_eq!(resolved.base_url, "http://file-url");
+        assert_eq!(resolved.source("base_url"), Source::File);
+        assert_eq!(resolved.format, "table");
+    }
+
+    #[test]
+    fn test_resolve_flag_from_env_reports_env() {
+        // clap copies ARKY_TOKEN into the --token flag value
+        let env = |var: &str| (var == "ARKY_TOKEN").then(|| "tok".to_string());
+        let flags = [None, None, None, Some("tok"), None];
+        let resolved = Config::resolve_from(flags, env, Config::default(), Config::default());
+        assert_eq!(resolved.token.as_deref(), Some("tok"));
+        assert_eq!(resolved.source("token"), Source::Env);
+    }
+
     #[test]
     fn test_require_business_id() {
         let resolved = ResolvedConfig {
             base_url: "http://localhost".into(),
             business_id: None,
+            business_key: None,
             token: None,
             format: "json".into(),
+            sources: Vec::new(),
         };
         assert!(resolved.require_business_id().is_err());
 
         let resolved2 = ResolvedConfig {
             base_url: "http://localhost".into(),
             business_id: Some("biz_1".into()),
+            business_key: None,
             token: None,
             format: "json".into(),
+            sources: Vec::new(),
         };
         assert_eq!(resolved2.require_business_id().unwrap(), "biz_1");
     }
+
+    #[test]
+    fn test_require_business_id_rejects_empty() {
+        for id in ["", "   "] {
+            let resolved = ResolvedConfig {
+                base_url: "http://localhost".into(),
+                business_id: Some(id.into()),
+                business_key: None,
+                token: None,
+                format: "json".into(),
+                sources: Vec::new(),
+            };
+            assert!(resolved.require_business_id().is_err());
+        }
+    }
 }
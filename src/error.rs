@@ -8,11 +8,22 @@ pub enum CliError {
         message: String,
         error: Option<String>,
         validation_errors: Vec<ValidationError>,
+        request_id: Option<String>,
     },
     Config(String),
     InvalidInput(String),
     Io(std::io::Error),
     Json(serde_json::Error),
+    /// Ctrl-C while waiting on the server, with the last value seen
+    Interrupted {
+        what: String,
+        last: serde_json::Value,
+    },
+    /// A non-JSON success body for --accept; main writes it out as-is
+    NotJson {
+        content_type: String,
+        body: Vec<u8>,
+    },
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -26,6 +37,7 @@ pub struct ValidationError {
 pub struct ApiErrorResponse {
     pub message: Option<String>,
     pub error: Option<String>,
+    #[allow(dead_code)]
     pub status_code: Option<u16>,
     #[serde(default)]
     pub validation_errors: Vec<ValidationError>,
@@ -34,17 +46,24 @@ pub struct ApiErrorResponse {
 impl fmt::Display for CliError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            CliError::Http(e) if e.is_timeout() => {
+                write!(f, "HTTP error: request timed out (raise it with --timeout <secs>)")
+            }
             CliError::Http(e) => write!(f, "HTTP error: {e}"),
             CliError::Api {
                 status,
                 message,
                 error,
                 validation_errors,
+                request_id,
             } => {
                 write!(f, "API error ({status}): {message}")?;
                 if let Some(err) = error {
                     write!(f, " [{err}]")?;
                 }
+                if let Some(id) = request_id {
+                    write!(f, " (request-id: {id})")?;
+                }
                 for ve in validation_errors {
                     write!(f, "\n  - {}: {}", ve.field, ve.error)?;
                 }
@@ -54,10 +73,21 @@ impl fmt::Display for CliError {
             CliError::InvalidInput(msg) => write!(f, "Invalid input: {msg}"),
             CliError::Io(e) => write!(f, "IO error: {e}"),
             CliError::Json(e) => write!(f, "JSON error: {e}"),
+            CliError::Interrupted { what, .. } => write!(f, "Interrupted while waiting for {what}"),
+            CliError::NotJson { content_type, .. } => {
+                write!(f, "Expected JSON but the response is {content_type}")
+            }
         }
     }
 }
 
+impl CliError {
+    /// The server couldn't be reached at all (refused, DNS failure, ...)
+    pub fn is_connect(&self) -> bool {
+        matches!(self, CliError::Http(e) if e.is_connect())
+    }
+}
+
 impl std::error::Error for CliError {}
 
 impl From<reqwest::Error> for CliError {
@@ -79,3 +109,23 @@ impl From<serde_json::Error> for CliError {
 }
 
 pub type Result<T> = std::result::Result<T, CliError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_error_display_includes_request_id() {
+        let err = CliError::Api {
+            status: 500,
+            message: "Internal error".into(),
+            error: None,
+            validation_errors: vec![],
+            request_id: Some("req_123".into()),
+        };
+        assert_eq!(
+            err.to_string(),
+            "API error (500): Internal error (request-id: req_123)"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+mod cache;
 mod client;
 mod commands;
 mod config;
@@ -11,8 +12,9 @@ use commands::{
     media::MediaCommand,
     network::NetworkCommand, node::NodeCommand, notification::NotificationCommand,
     order::OrderCommand, platform::PlatformCommand, product::ProductCommand,
-    promo_code::PromoCodeCommand, provider::ProviderCommand, service::ServiceCommand,
-    shipping::ShippingCommand, workflow::WorkflowCommand,
+    promo_code::PromoCodeCommand, provider::ProviderCommand, raw::RawCommand,
+    service::ServiceCommand,
+    shipping::ShippingCommand, workflow::WorkflowCommand, Example,
 };
 
 /// Arky CLI — agentic-first CLI for the Arky platform.
@@ -25,38 +27,90 @@ use commands::{
 ///   arky node create --help          # see exact JSON shape for creating nodes
 ///   arky product create --help       # see required fields for products
 ///   arky notification trigger --help  # see how to send emails
+///   arky raw GET /v1/businesses/{biz}/nodes  # call any endpoint directly
 ///
 /// Setup:
 ///   arky config set base_url http://localhost:8000
 ///   arky config set business_id YOUR_BUSINESS_ID
+///   arky config set business_key my-shop   # alternative: resolved to the ID
 ///   arky auth login your@email.com          # sends verification code
 ///   arky auth verify your@email.com CODE    # saves token automatically
 ///
 /// Or via environment variables:
 ///   export ARKY_BASE_URL=http://localhost:8000
-///   export ARKY_BUSINESS_ID=your-business-id
+///   export ARKY_BUSINESS_ID=your-business-id   # or ARKY_BUSINESS_KEY=my-shop
 ///   export ARKY_TOKEN=your-api-token
+///   Or put them in ./.env (loaded automatically) or a file passed with --env-file.
+///   Precedence: flags > exported env vars > env file > ~/.arky/config.json
+///   Without a base_url, http://localhost:8000 is used. Blank values count as
+///   unset; `arky config show --explain` shows where each value came from.
 ///
 /// Authentication:
 ///   Method 1: Email magic link (arky auth login + arky auth verify)
 ///   Method 2: API token via --token flag or ARKY_TOKEN env var
+///     --token - (or --token-stdin) reads it from stdin instead of argv; on a
+///     terminal it prompts without echo. Piped, only the first line is used:
+///       printf '%s\n' "$TOKEN" | arky --token - node list
 ///   Method 3: Anonymous session (arky auth session)
+///   Profiles: auth verify/session --save-profile NAME stores the login in
+///     ~/.arky/profiles/NAME.json; --profile NAME (or ARKY_PROFILE) uses it.
+///   --config PATH (or ARKY_CONFIG) reads and writes that file instead of
+///     ~/.arky/config.json, e.g. a per-project config or one for tests.
+///   Gateways needing extra headers: --header "X-Api-Key: ..." (repeatable)
 ///
 /// Data input (--data flag):
 ///   Inline JSON:  --data '{"key": "value"}'
 ///   From file:    --data @content.json
 ///   From stdin:   echo '{}' | arky <cmd> --data -
+///   --expand-env replaces ${VAR} (or ${VAR:-default}) with environment values
+///   before parsing; values are inserted as-is, so quote them inside JSON
+///   strings ("${NAME}"). Write $${ for a literal ${.
+///   A KEY or ID argument wins over "key"/"id" in --data; other fields the
+///   CLI sets (e.g. businessId) take the --data value. Both cases warn.
 ///
 /// Output formats (--format):
 ///   json   - Pretty JSON (default, best for AI agents)
 ///   table  - Human-readable table
 ///   plain  - Key=value pairs for piping
+///   jsonl  - One compact JSON line per list item (same as --ndjson)
+///   In json mode, confirmations go to stderr as {"success": true, "message": "..."}.
+///   --compact prints single-line JSON; --ndjson prints list items one per line.
+///   With --ndjson (or jsonl), list --all streams each page as it arrives.
+///   Tables fit the terminal width; --max-width N sets it explicitly.
+///   Numeric table columns are right-aligned; --humanize shows amount/total/
+///   subtotal/tax/discount/price (minor units) as 53.98 and groups digits.
+///   --format plain --deep flattens nested values: total.amount=5398, items[0].sku=A.
+///   --pointer /data/0/id prints just the value at that JSON Pointer (RFC 6901),
+///   in the chosen format; --format plain prints strings bare:
+///     ID=$(arky product list --limit 1 --pointer /data/0/id --format plain)
+///   --template "{id}: {status} {total.amount}" prints one line per item instead
+///   (missing fields print --template-missing, default empty; {{ }} for braces).
+///   --accept text/csv asks the API for another representation; a non-JSON
+///   response is printed as-is, or saved with --output FILE:
+///     arky order list --accept text/csv --output orders.csv
+///
+/// Chaining creates:
+///   With --remember-id, each create saves its ID to ~/.arky/cache/last.json;
+///   @last in any ID position then stands for it:
+///     arky node create hero --remember-id --data @hero.json
+///     arky node update @last --data '{"status": "active"}'
+///   @last after --data/--input still means a file named "last".
+///
+/// Lists:
+///   --limit N is one page of N (default 20); continue with --cursor.
+///   --all (or --limit 0) follows every page, --page-size items per request;
+///   with --all, --limit caps the total. --count prints only the number.
 ///
 /// API conventions:
-///   - All updates are full PUT (replace entire object, no merge/patch).
-///     Always GET the current object first, modify it, then PUT the full thing back.
+///   - Updates are full PUT (replace entire object): GET the current object
+///     first, modify it, then PUT the full thing back. node/product/service/order
+///     update --patch sends only the given fields via PATCH instead.
 ///   - All monetary values are i64 in minor units (e.g. 5000 = $50.00 USD).
 ///     Minor units = smallest currency unit (cents for USD, pence for GBP, yen for JPY).
+///   - `create`/`update --example` prints a --data skeleton to edit and exits
+///     (e.g. arky product create --example > product.json).
+///   - `delete --ignore-missing` treats a 404 as already deleted, for teardown
+///     scripts that may run twice.
 ///   - Timestamps are Unix epoch seconds (i64). Server sets createdAt/updatedAt.
 ///   - IDs are UUIDs. Keys are human-readable slugs (letters, numbers, _ and -).
 ///   - Keys must be unique per business (duplicate key = error).
@@ -70,6 +124,8 @@ use commands::{
 ///     Conditions without `input` are rejected at create/update time.
 ///   - Media references must use format `media:<uuid>` (e.g. media:550e8400-...).
 ///     Invalid formats or non-UUID values are rejected.
+///   - The API ignores unknown fields. --strict rejects typo'd top-level keys
+///     (e.g. "blockz") in node/product/service/provider --data before sending.
 ///   - Node keys must be URL-safe (letters, numbers, _ and -), max 255 chars.
 ///     Enforced on both create and update.
 ///   - Zones/locations must be configured in business settings before accepting orders.
@@ -81,7 +137,7 @@ use commands::{
 #[derive(Parser, Debug)]
 #[command(name = "arky", version, about, long_about)]
 struct Cli {
-    /// Server base URL
+    /// Server base URL (default http://localhost:8000)
     #[arg(long, global = true, env = "ARKY_BASE_URL")]
     base_url: Option<String>,
 
@@ -89,14 +145,115 @@ struct Cli {
     #[arg(long, global = true, env = "ARKY_BUSINESS_ID")]
     business_id: Option<String>,
 
-    /// Auth token
+    /// Business key (e.g. my-shop), used when no business ID is set
+    #[arg(long, global = true, env = "ARKY_BUSINESS_KEY")]
+    business_key: Option<String>,
+
+    /// Auth token; - reads it from stdin (a terminal prompts without echo)
     #[arg(long, global = true, env = "ARKY_TOKEN")]
     token: Option<String>,
 
-    /// Output format: json (default), table, plain
-    #[arg(long, global = true, env = "ARKY_FORMAT", default_value = "json")]
+    /// Read the auth token from stdin, same as --token -
+    #[arg(long, global = true)]
+    token_stdin: bool,
+
+    /// Config file to use instead of ~/.arky/config.json (overrides --profile)
+    #[arg(long, global = true, env = "ARKY_CONFIG", value_name = "PATH")]
+    config: Option<String>,
+
+    /// Use ~/.arky/profiles/NAME.json, falling back to the config file for unset keys
+    #[arg(long, global = true, env = "ARKY_PROFILE", value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Load ARKY_* variables from a dotenv file (default: ./.env if present).
+    /// Exported variables and flags take precedence over the file.
+    #[arg(long, global = true, value_name = "PATH")]
+    env_file: Option<String>,
+
+    /// Request timeout in seconds (default 60; `agent run` defaults to 600)
+    #[arg(long, global = true, env = "ARKY_TIMEOUT")]
+    timeout: Option<u64>,
+
+    /// Upload timeout in seconds for `media upload` (default 300, or --timeout)
+    #[arg(long, global = true, env = "ARKY_UPLOAD_TIMEOUT", value_name = "SECS")]
+    upload_timeout: Option<u64>,
+
+    /// Print time spent in HTTP requests to stderr ("elapsed: 123ms")
+    #[arg(long, global = true)]
+    time: bool,
+
+    /// With JSON output, add the request time as `_meta.elapsedMs` on object responses
+    #[arg(long, global = true)]
+    meta: bool,
+
+    /// Don't request compressed (gzip/brotli/deflate) responses
+    #[arg(long, global = true, env = "ARKY_NO_COMPRESS")]
+    no_compress: bool,
+
+    /// User-Agent header (default arky-cli/<version>)
+    #[arg(long, global = true, env = "ARKY_USER_AGENT")]
+    user_agent: Option<String>,
+
+    /// Request another media type (e.g. text/csv); non-JSON responses print as-is
+    #[arg(long, global = true, value_name = "MIME")]
+    accept: Option<String>,
+
+    /// Save a non-JSON (--accept) response body to FILE instead of printing it
+    #[arg(long, global = true, value_name = "FILE", requires = "accept")]
+    output: Option<String>,
+
+    /// Extra request header as "Name: value" (repeatable)
+    #[arg(long = "header", global = true, value_name = "NAME: VALUE")]
+    headers: Vec<String>,
+
+    /// Output format: json (default), table, plain, jsonl
+    #[arg(long, global = true, env = "ARKY_FORMAT")]
     format: Option<String>,
 
+    /// Print JSON on a single line instead of pretty-printed
+    #[arg(long, global = true)]
+    compact: bool,
+
+    /// Print list results as newline-delimited JSON (one object per line)
+    #[arg(long, global = true)]
+    ndjson: bool,
+
+    /// Reject unknown top-level --data fields for nodes, products, services, providers
+    #[arg(long, global = true, env = "ARKY_STRICT")]
+    strict: bool,
+
+    /// Save the ID of each create so later commands can refer to it as @last
+    #[arg(long, global = true, env = "ARKY_REMEMBER_ID")]
+    remember_id: bool,
+
+    /// Substitute ${VAR} and ${VAR:-default} in --data from the environment
+    #[arg(long, global = true, env = "ARKY_EXPAND_ENV")]
+    expand_env: bool,
+
+    /// With --format plain, flatten nested objects/arrays into dotted keys
+    #[arg(long, global = true)]
+    deep: bool,
+
+    /// Print only the value at a JSON Pointer (RFC 6901) in the final result, e.g. /data/0/id
+    #[arg(long, global = true, visible_alias = "json-pointer", value_name = "POINTER")]
+    pointer: Option<String>,
+
+    /// Print one line per item from a "{field} {nested.path}" template
+    #[arg(long, global = true, visible_alias = "output-template", value_name = "TEMPLATE")]
+    template: Option<String>,
+
+    /// Text for --template placeholders whose path is missing or null
+    #[arg(long, global = true, default_value = "", requires = "template")]
+    template_missing: String,
+
+    /// Table width in characters (default: terminal width)
+    #[arg(long, global = true, value_name = "N")]
+    max_width: Option<usize>,
+
+    /// Tables: show money fields (cents) as 53.98 and group digits: 1,000
+    #[arg(long, global = true)]
+    humanize: bool,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -199,26 +356,190 @@ enum Command {
         #[command(subcommand)]
         cmd: NotificationCommand,
     },
+    /// Call any API endpoint directly (escape hatch for unwrapped endpoints)
+    #[command(long_about = "Issue a request to any API path using the configured auth.\n\n\
+        The token {biz} in the path is replaced with the current business ID.\n\n\
+        Examples:\n\
+        arky raw GET /v1/platform/currencies\n\
+        arky raw GET /v1/businesses/{biz}/nodes --query limit=5 --query type=blog\n\
+        arky raw POST /v1/businesses/{biz}/orders/quote --data @quote.json\n\
+        arky raw DELETE /v1/businesses/{biz}/nodes/NODE_ID")]
+    Raw {
+        #[command(flatten)]
+        cmd: RawCommand,
+    },
+}
+
+impl Command {
+    /// Commands that reach endpoints usable without a token, or send nothing.
+    /// `workflow trigger` is authenticated by its secret; only `--id` needs a token.
+    fn needs_token(&self) -> bool {
+        !self.is_example()
+            && !matches!(
+                self,
+                Command::Config { .. }
+                    | Command::Platform { .. }
+                    | Command::Network { .. }
+                    | Command::Raw { .. }
+                    | Command::Auth {
+                        cmd: AuthCommand::Login { .. }
+                            | AuthCommand::Verify { .. }
+                            | AuthCommand::Session { .. }
+                    }
+                    | Command::Workflow {
+                        cmd: WorkflowCommand::Trigger { secret: Some(_), .. }
+                    }
+            )
+    }
+
+    /// Commands that address a business, so a --business-key must be resolved.
+    /// `raw` only needs it when its path uses the {biz} placeholder.
+    fn needs_business(&self) -> bool {
+        match self {
+            Command::Raw { cmd } => cmd.path.contains("{biz}"),
+            _ => !matches!(
+                self,
+                Command::Config { .. }
+                    | Command::Auth { .. }
+                    | Command::Platform { .. }
+                    | Command::Network { .. }
+            ),
+        }
+    }
+
+    /// `create/update --example` prints a skeleton without calling the API
+    fn is_example(&self) -> bool {
+        match self {
+            Command::Agent { cmd } => cmd.is_example(),
+            Command::Business { cmd } => cmd.is_example(),
+            Command::Node { cmd } => cmd.is_example(),
+            Command::Product { cmd } => cmd.is_example(),
+            Command::Order { cmd } => cmd.is_example(),
+            Command::Workflow { cmd } => cmd.is_example(),
+            Command::Service { cmd } => cmd.is_example(),
+            Command::Provider { cmd } => cmd.is_example(),
+            Command::Booking { cmd } => cmd.is_example(),
+            Command::Audience { cmd } => cmd.is_example(),
+            Command::PromoCode { cmd } => cmd.is_example(),
+            _ => false,
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() {
-    let cli = Cli::parse();
+    let args = match commands::resolve_last_id(std::env::args().collect()) {
+        Ok(args) => args,
+        Err(e) => {
+            output::print_error(&e.to_string());
+            std::process::exit(1);
+        }
+    };
+    let mut cli = Cli::parse_from(&args);
+    // Flags read their env fallbacks at parse time, so parse again once the file is loaded
+    match config::load_env_file(cli.env_file.as_deref()) {
+        Ok(true) => cli = Cli::parse_from(&args),
+        Ok(false) => {}
+        Err(e) => {
+            output::print_error(&e.to_string());
+            std::process::exit(1);
+        }
+    }
+
+    config::set_config_file(cli.config.clone());
+    if let Err(e) = config::set_profile(cli.profile.clone()) {
+        output::print_error(&e.to_string());
+        std::process::exit(1);
+    }
+
+    // Keep the secret out of argv and shell history
+    if cli.token_stdin || cli.token.as_deref() == Some("-") {
+        match config::read_token_from_stdin() {
+            Ok(token) => cli.token = Some(token),
+            Err(e) => {
+                output::print_error(&e.to_string());
+                std::process::exit(1);
+            }
+        }
+    }
 
     let resolved = config::Config::resolve(
         cli.base_url.as_deref(),
         cli.business_id.as_deref(),
+        cli.business_key.as_deref(),
         cli.token.as_deref(),
         cli.format.as_deref(),
     );
 
     let format = output::Format::from_str(&resolved.format);
+    output::set_format(&format);
+    let jsonl = resolved.format.eq_ignore_ascii_case("jsonl");
+    output::set_json_style(cli.compact, cli.ndjson || jsonl);
+    output::set_max_width(cli.max_width);
+    output::set_humanize(cli.humanize);
+    output::set_meta(cli.meta);
+    output::set_deep_plain(cli.deep);
+    output::set_template(cli.template.clone(), cli.template_missing.clone());
+    if let Err(e) = output::set_pointer(cli.pointer.clone()) {
+        output::print_error(&e.to_string());
+        std::process::exit(1);
+    }
+    commands::set_expand_env(cli.expand_env);
+    commands::set_remember_id(cli.remember_id);
+    commands::set_strict(cli.strict);
+
+    let mut extra_headers = reqwest::header::HeaderMap::new();
+    for arg in &cli.headers {
+        match client::parse_header(arg) {
+            Ok((name, value)) => {
+                extra_headers.insert(name, value);
+            }
+            Err(e) => {
+                output::print_error(&e.to_string());
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let accept = cli.accept.as_deref().map(reqwest::header::HeaderValue::from_str);
+    let accept = match accept.transpose() {
+        Ok(accept) => accept,
+        Err(_) => {
+            output::print_error("Invalid --accept value");
+            std::process::exit(1);
+        }
+    };
+
+    // Fail early with a hint instead of a bare 401; an Authorization --header counts as auth
+    if cli.command.needs_token() && !extra_headers.contains_key(reqwest::header::AUTHORIZATION) {
+        if let Err(e) = resolved.require_token() {
+            output::print_error(&e.to_string());
+            std::process::exit(1);
+        }
+    }
 
-    let client = client::ArkyClient::new(
+    let mut client = client::ArkyClient::new(
         resolved.base_url.clone(),
         resolved.business_id.clone(),
         resolved.token.clone(),
-    );
+    )
+    .with_timeout(cli.timeout.map(std::time::Duration::from_secs))
+    .with_upload_timeout(cli.upload_timeout.map(std::time::Duration::from_secs))
+    .with_compression(!cli.no_compress)
+    .with_user_agent(cli.user_agent.clone())
+    .with_headers(extra_headers);
+
+    let needs_business = cli.command.needs_business();
+    if let (Err(_), Some(key), true) =
+        (resolved.require_business_id(), &resolved.business_key, needs_business)
+    {
+        if let Err(e) = client.resolve_business_key(key).await {
+            output::print_error(&e.to_string());
+            std::process::exit(1);
+        }
+    }
+    // Set after the business lookup above, which needs a JSON answer
+    let client = client.with_accept(accept);
 
     let result = match cli.command {
         Command::Agent { cmd } => commands::agent::handle(cmd, &client, &format).await,
@@ -242,10 +563,53 @@ async fn main() {
         Command::Notification { cmd } => {
             commands::notification::handle(cmd, &client, &format).await
         }
+        Command::Raw { cmd } => commands::raw::handle(cmd, &client, &format).await,
+    };
+
+    if cli.time {
+        eprintln!("elapsed: {}ms", client::http_elapsed().as_millis());
+    }
+
+    // An --accept body the server sent as-is is the result, not a failure
+    let result = match result {
+        Err(error::CliError::NotJson { content_type, body }) => {
+            output::print_raw(&content_type, &body, cli.output.as_deref())
+        }
+        other => other,
     };
 
     if let Err(e) = result {
         output::print_error(&e.to_string());
+        if let error::CliError::Interrupted { last, .. } = &e {
+            // The last state seen, so a script can tell how far it got
+            eprintln!("{}", serde_json::to_string_pretty(last).unwrap_or_default());
+            std::process::exit(130);
+        }
+        if e.is_connect() {
+            output::print_warning(&format!(
+                "Could not reach {}. Point arky at your server with \
+                 `arky config set base_url URL`, ARKY_BASE_URL or --base-url",
+                resolved.base_url
+            ));
+        }
         std::process::exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(args: &[&str]) -> Command {
+        Cli::try_parse_from(std::iter::once("arky").chain(args.iter().copied()))
+            .unwrap()
+            .command
+    }
+
+    #[test]
+    fn test_workflow_trigger_needs_token_only_with_id() {
+        assert!(!command(&["workflow", "trigger", "sec_1"]).needs_token());
+        assert!(command(&["workflow", "trigger", "--id", "wf_1"]).needs_token());
+        assert!(command(&["workflow", "list"]).needs_token());
+    }
+}
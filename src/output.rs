@@ -1,5 +1,36 @@
 use colored::Colorize;
 use serde_json::Value;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// Whether confirmations go to stderr as JSON (set once from --format in main)
+static JSON_MESSAGES: AtomicBool = AtomicBool::new(false);
+/// --compact: single-line JSON
+static COMPACT: AtomicBool = AtomicBool::new(false);
+/// --ndjson: one compact JSON object per line for list output
+static NDJSON: AtomicBool = AtomicBool::new(false);
+/// --meta: add `_meta.elapsedMs` to JSON object output
+static META: AtomicBool = AtomicBool::new(false);
+/// --deep: `--format plain` flattens nested values into dotted keys
+static DEEP_PLAIN: AtomicBool = AtomicBool::new(false);
+/// --template and --template-missing: one formatted line per item instead of the format
+static TEMPLATE: OnceLock<(String, String)> = OnceLock::new();
+/// --pointer: RFC 6901 JSON Pointer selecting the part of the result to print
+static POINTER: OnceLock<String> = OnceLock::new();
+/// --max-width: character budget for table rows (0 = terminal width)
+static MAX_WIDTH: AtomicUsize = AtomicUsize::new(0);
+/// --humanize: table money fields as 53.98 and numbers with thousands separators
+static HUMANIZE: AtomicBool = AtomicBool::new(false);
+
+/// Table width when stdout is not a terminal and --max-width is unset
+const FALLBACK_TABLE_WIDTH: usize = 120;
+/// Narrowest a column is squeezed to; leaves room for one char plus "..."
+const MIN_COLUMN_WIDTH: usize = 4;
+/// Rows checked when deciding whether a table column is numeric
+const NUMERIC_SAMPLE_ROWS: usize = 20;
+/// Fields holding amounts in minor units (cents), shown as 53.98 with --humanize
+const MONEY_FIELDS: &[&str] = &["amount", "total", "subtotal", "tax", "discount", "price"];
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Format {
@@ -18,21 +49,216 @@ impl Format {
     }
 }
 
-pub fn print_output(value: &Value, format: &Format) {
+/// Print a command's final result; --pointer selects the part to print
+pub fn print_output(value: &Value, format: &Format) -> crate::error::Result<()> {
+    let value = match POINTER.get() {
+        Some(pointer) => value.pointer(pointer).ok_or_else(|| {
+            crate::error::CliError::InvalidInput(format!("Nothing at --pointer {pointer}"))
+        })?,
+        None => value,
+    };
+    print_preview(value, format);
+    Ok(())
+}
+
+/// Print output shown on the way to the result, like a diff before asking to
+/// apply it. --pointer addresses the final result, so it is not applied here.
+pub fn print_preview(value: &Value, format: &Format) {
+    if let Some((template, missing)) = TEMPLATE.get() {
+        let items = value.as_array().or_else(|| value["data"].as_array());
+        for item in items.map_or_else(|| vec![value], |items| items.iter().collect()) {
+            println!("{}", render_template(template, item, missing));
+        }
+        return;
+    }
+    let with_meta;
+    let value = if *format == Format::Json && META.load(Ordering::Relaxed) {
+        with_meta = add_meta(value, crate::client::http_elapsed());
+        &with_meta
+    } else {
+        value
+    };
     match format {
+        Format::Json if NDJSON.load(Ordering::Relaxed) => {
+            for line in ndjson_lines(value) {
+                println!("{line}");
+            }
+        }
+        Format::Json if COMPACT.load(Ordering::Relaxed) => println!("{value}"),
         Format::Json => {
-            println!(
-                "{}",
-                serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
-            );
+            if std::io::stdout().is_terminal()
+                && colored::control::SHOULD_COLORIZE.should_colorize()
+            {
+                println!("{}", colorize_json(value, 0));
+            } else {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+                );
+            }
         }
         Format::Table => print_table(value),
         Format::Plain => print_plain(value),
     }
 }
 
+/// Write a non-JSON response body as-is: to the `out` file, else stdout
+pub fn print_raw(content_type: &str, body: &[u8], out: Option<&str>) -> crate::error::Result<()> {
+    match out {
+        Some(path) => {
+            crate::commands::write_out(path, body)?;
+            print_success(&format!("Saved {} bytes ({content_type}) to {path}", body.len()));
+        }
+        None => crate::commands::write_out("-", body)?,
+    }
+    Ok(())
+}
+
+/// JSON layout options from --compact / --ndjson
+pub fn set_json_style(compact: bool, ndjson: bool) {
+    COMPACT.store(compact, Ordering::Relaxed);
+    NDJSON.store(ndjson, Ordering::Relaxed);
+}
+
+/// --pointer must be empty (the whole result) or start with "/"
+pub fn set_pointer(pointer: Option<String>) -> crate::error::Result<()> {
+    if let Some(pointer) = pointer {
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return Err(crate::error::CliError::InvalidInput(format!(
+                "Invalid --pointer '{pointer}'. JSON Pointers start with /, e.g. /data/0/id"
+            )));
+        }
+        let _ = POINTER.set(pointer);
+    }
+    Ok(())
+}
+
+pub fn set_template(template: Option<String>, missing: String) {
+    if let Some(template) = template {
+        let _ = TEMPLATE.set((template, missing));
+    }
+}
+
+/// Fill `{dotted.path}` placeholders from `item` (`{items.0.sku}` indexes
+/// arrays). Strings print bare, objects/arrays as compact JSON; absent or null
+/// paths print `missing`. `{{` and `}}` are literal braces.
+fn render_template(template: &str, item: &Value, missing: &str) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let Some(end) = tail.find('}').filter(|_| tail.starts_with('{')) else {
+            out.push_str(&tail[..1]);
+            rest = &tail[1..];
+            continue;
+        };
+        let path = tail[1..end].trim();
+        let found = path.split('.').try_fold(item, |v, key| match v {
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => v.get(key),
+        });
+        match found {
+            None | Some(Value::Null) => out.push_str(missing),
+            Some(Value::String(s)) => out.push_str(s),
+            Some(other) => out.push_str(&other.to_string()),
+        }
+        rest = &tail[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+pub fn set_deep_plain(enabled: bool) {
+    DEEP_PLAIN.store(enabled, Ordering::Relaxed);
+}
+
+pub fn set_meta(enabled: bool) {
+    META.store(enabled, Ordering::Relaxed);
+}
+
+/// Attach `_meta.elapsedMs` to an object; arrays and scalars are left as-is
+fn add_meta(value: &Value, elapsed: std::time::Duration) -> Value {
+    let mut value = value.clone();
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert(
+            "_meta".into(),
+            serde_json::json!({ "elapsedMs": elapsed.as_millis() as u64 }),
+        );
+    }
+    value
+}
+
+pub fn set_max_width(width: Option<usize>) {
+    MAX_WIDTH.store(width.unwrap_or(0), Ordering::Relaxed);
+}
+
+pub fn set_humanize(enabled: bool) {
+    HUMANIZE.store(enabled, Ordering::Relaxed);
+}
+
+fn table_width() -> usize {
+    match MAX_WIDTH.load(Ordering::Relaxed) {
+        0 => terminal_size::terminal_size()
+            .map(|(w, _)| w.0 as usize)
+            .unwrap_or(FALLBACK_TABLE_WIDTH),
+        n => n,
+    }
+}
+
+/// Arrays (and `data` arrays of list responses) become one line per item;
+/// anything else is a single compact line
+fn ndjson_lines(value: &Value) -> Vec<String> {
+    let items = value.as_array().or_else(|| value["data"].as_array());
+    match items {
+        Some(items) => items.iter().map(|item| item.to_string()).collect(),
+        None => vec![value.to_string()],
+    }
+}
+
+/// Whether `--all` lists print each page as it arrives (--ndjson or --format jsonl)
+pub fn streams_lists() -> bool {
+    JSON_MESSAGES.load(Ordering::Relaxed)
+        && NDJSON.load(Ordering::Relaxed)
+        && TEMPLATE.get().is_none()
+        && POINTER.get().is_none()
+}
+
+/// Print one page of a streamed list, one compact line per item
+pub fn stream_items(items: &[Value]) {
+    for item in items {
+        println!("{item}");
+    }
+}
+
+/// Route `print_success`/`print_warning` through the chosen output format
+pub fn set_format(format: &Format) {
+    JSON_MESSAGES.store(*format == Format::Json, Ordering::Relaxed);
+}
+
 pub fn print_success(msg: &str) {
-    eprintln!("{} {}", "OK".green().bold(), msg);
+    if JSON_MESSAGES.load(Ordering::Relaxed) {
+        eprintln!("{}", success_json(msg));
+    } else {
+        eprintln!("{} {}", "OK".green().bold(), msg);
+    }
+}
+
+pub fn print_warning(msg: &str) {
+    if JSON_MESSAGES.load(Ordering::Relaxed) {
+        eprintln!("{}", serde_json::json!({ "warning": msg }));
+    } else {
+        eprintln!("{} {}", "WARN".yellow().bold(), msg);
+    }
+}
+
+fn success_json(msg: &str) -> Value {
+    serde_json::json!({ "success": true, "message": msg })
 }
 
 pub fn print_error(msg: &str) {
@@ -51,12 +277,11 @@ fn print_table(value: &Value) {
             let keys: Vec<String> = if let Some(Value::Object(obj)) = items.first() {
                 obj.keys()
                     .filter(|k| {
-                        // Skip large nested objects in table view
-                        if let Some(first) = items.first() {
-                            !matches!(first.get(k.as_str()), Some(Value::Array(_)) | Some(Value::Object(_)))
-                        } else {
-                            true
-                        }
+                        // Skip nested values that would only show as {...} or [N items]
+                        items
+                            .first()
+                            .and_then(|first| first.get(k.as_str()))
+                            .is_none_or(has_summary)
                     })
                     .cloned()
                     .collect()
@@ -73,20 +298,31 @@ fn print_table(value: &Value) {
                 return;
             }
 
-            // Calculate column widths
+            // Calculate column widths, then squeeze them into the table width
+            let cell = |item: &Value, key: &str| {
+                table_cell(key, item.get(key).unwrap_or(&Value::Null))
+            };
             let mut widths: Vec<usize> = keys.iter().map(|k| k.len()).collect();
             for item in items {
                 for (i, key) in keys.iter().enumerate() {
-                    let cell = format_cell(item.get(key).unwrap_or(&Value::Null));
-                    widths[i] = widths[i].max(cell.len().min(40));
+                    widths[i] = widths[i].max(cell(item, key).chars().count());
                 }
             }
+            fit_widths(&mut widths, table_width());
+            let numeric: Vec<bool> = keys.iter().map(|k| is_numeric_column(items, k)).collect();
+            let align = |text: String, i: usize| {
+                if numeric[i] {
+                    format!("{:>width$}", text, width = widths[i])
+                } else {
+                    format!("{:width$}", text, width = widths[i])
+                }
+            };
 
             // Print header
             let header: Vec<String> = keys
                 .iter()
                 .enumerate()
-                .map(|(i, k)| format!("{:width$}", k.to_uppercase(), width = widths[i]))
+                .map(|(i, k)| align(truncate_cell(k.to_uppercase(), widths[i]), i))
                 .collect();
             println!("{}", header.join("  ").bold());
             let sep: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
@@ -97,28 +333,18 @@ fn print_table(value: &Value) {
                 let row: Vec<String> = keys
                     .iter()
                     .enumerate()
-                    .map(|(i, key)| {
-                        let cell = format_cell(item.get(key).unwrap_or(&Value::Null));
-                        let truncated = if cell.len() > 40 {
-                            format!("{}...", &cell[..37])
-                        } else {
-                            cell
-                        };
-                        format!("{:width$}", truncated, width = widths[i])
-                    })
+                    .map(|(i, key)| align(truncate_cell(cell(item, key), widths[i]), i))
                     .collect();
                 println!("{}", row.join("  "));
             }
         }
         Value::Object(obj) => {
             let max_key_len = obj.keys().map(|k| k.len()).max().unwrap_or(0);
+            let value_width = table_width()
+                .saturating_sub(max_key_len + 2)
+                .max(MIN_COLUMN_WIDTH);
             for (key, val) in obj {
-                let display = format_cell(val);
-                let truncated = if display.len() > 80 {
-                    format!("{}...", &display[..77])
-                } else {
-                    display
-                };
+                let truncated = truncate_cell(table_cell(key, val), value_width);
                 println!(
                     "{:>width$}  {}",
                     key.bold(),
@@ -131,6 +357,38 @@ fn print_table(value: &Value) {
     }
 }
 
+/// Pretty-print JSON with ANSI colors. Layout matches `to_string_pretty`,
+/// so stripping the escape codes yields the same text.
+fn colorize_json(value: &Value, indent: usize) -> String {
+    let pad = "  ".repeat(indent + 1);
+    let close_pad = "  ".repeat(indent);
+    match value {
+        Value::Null => "null".magenta().to_string(),
+        Value::Bool(b) => b.to_string().magenta().to_string(),
+        Value::Number(n) => n.to_string().yellow().to_string(),
+        Value::String(_) => value.to_string().green().to_string(),
+        Value::Array(items) if items.is_empty() => "[]".to_string(),
+        Value::Object(obj) if obj.is_empty() => "{}".to_string(),
+        Value::Array(items) => {
+            let inner: Vec<String> = items
+                .iter()
+                .map(|item| format!("{pad}{}", colorize_json(item, indent + 1)))
+                .collect();
+            format!("[\n{}\n{close_pad}]", inner.join(",\n"))
+        }
+        Value::Object(obj) => {
+            let inner: Vec<String> = obj
+                .iter()
+                .map(|(k, v)| {
+                    let key = Value::String(k.clone()).to_string();
+                    format!("{pad}{}: {}", key.blue().bold(), colorize_json(v, indent + 1))
+                })
+                .collect();
+            format!("{{\n{}\n{close_pad}}}", inner.join(",\n"))
+        }
+    }
+}
+
 fn print_plain(value: &Value) {
     match value {
         Value::String(s) => println!("{s}"),
@@ -142,6 +400,13 @@ fn print_plain(value: &Value) {
                 print_plain(item);
             }
         }
+        Value::Object(_) if DEEP_PLAIN.load(Ordering::Relaxed) => {
+            let mut lines = Vec::new();
+            flatten_plain("", value, &mut lines);
+            for line in lines {
+                println!("{line}");
+            }
+        }
         Value::Object(obj) => {
             for (key, val) in obj {
                 println!("{}={}", key, format_cell(val));
@@ -150,14 +415,145 @@ fn print_plain(value: &Value) {
     }
 }
 
+/// `key=value` lines for every scalar under `value`: objects add `.key`,
+/// arrays add `[i]`; empty containers print as `{}` / `[]`
+fn flatten_plain(prefix: &str, value: &Value, lines: &mut Vec<String>) {
+    match value {
+        Value::Object(obj) if !obj.is_empty() => {
+            for (key, val) in obj {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten_plain(&path, val, lines);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, item) in items.iter().enumerate() {
+                flatten_plain(&format!("{prefix}[{i}]"), item, lines);
+            }
+        }
+        Value::String(s) => lines.push(format!("{prefix}={s}")),
+        other => lines.push(format!("{prefix}={other}")),
+    }
+}
+
+/// A column is numeric when its sampled non-null values are all numbers
+fn is_numeric_column(items: &[Value], key: &str) -> bool {
+    let mut values = items
+        .iter()
+        .take(NUMERIC_SAMPLE_ROWS)
+        .filter_map(|item| item.get(key))
+        .filter(|v| !v.is_null())
+        .peekable();
+    values.peek().is_some() && values.all(Value::is_number)
+}
+
+/// A table cell; with --humanize, integers get thousands separators and
+/// money fields (minor units) a decimal point: 539800 -> 5,398.00
+fn table_cell(key: &str, value: &Value) -> String {
+    let humanize = HUMANIZE.load(Ordering::Relaxed);
+    match value.as_i64() {
+        Some(n) if humanize => humanize_number(n, MONEY_FIELDS.contains(&key)),
+        _ => format_cell(value),
+    }
+}
+
+fn humanize_number(n: i64, money: bool) -> String {
+    let abs = n.unsigned_abs();
+    let (whole, cents) = if money {
+        (abs / 100, Some(abs % 100))
+    } else {
+        (abs, None)
+    };
+    let digits = whole.to_string();
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    let sign = if n < 0 { "-" } else { "" };
+    match cents {
+        Some(cents) => format!("{sign}{grouped}.{cents:02}"),
+        None => format!("{sign}{grouped}"),
+    }
+}
+
 fn format_cell(value: &Value) -> String {
     match value {
         Value::String(s) => s.clone(),
         Value::Number(n) => n.to_string(),
         Value::Bool(b) => b.to_string(),
         Value::Null => "-".to_string(),
+        Value::Array(a) if !a.is_empty() && a.iter().all(is_block) => {
+            format!("{} blocks", a.len())
+        }
         Value::Array(a) => format!("[{} items]", a.len()),
-        Value::Object(_) => "{...}".to_string(),
+        Value::Object(obj) => format_object_cell(obj),
+    }
+}
+
+/// Whether a table cell says more than `{...}` / `[N items]`
+fn has_summary(value: &Value) -> bool {
+    match value {
+        Value::Array(a) => !a.is_empty() && a.iter().all(is_block),
+        Value::Object(obj) => format_object_cell(obj) != "{...}",
+        _ => true,
+    }
+}
+
+/// Content blocks carry at least a string `type` and a `key`
+fn is_block(value: &Value) -> bool {
+    value["type"].is_string() && value["key"].is_string()
+}
+
+/// Readable summaries for the object shapes content entities use:
+/// a block shows its value, geo locations show `lat,lon`, and
+/// localized text / string maps show one locale (`en` first).
+fn format_object_cell(obj: &serde_json::Map<String, Value>) -> String {
+    if obj.get("type").is_some_and(Value::is_string) && obj.contains_key("value") {
+        return format_cell(&obj["value"]);
+    }
+    let coord = |names: &[&str]| names.iter().find_map(|n| obj.get(*n).and_then(Value::as_f64));
+    if let (Some(lat), Some(lon)) = (
+        coord(&["lat", "latitude"]),
+        coord(&["lon", "lng", "longitude"]),
+    ) {
+        return format!("{lat},{lon}");
+    }
+    if !obj.is_empty() && obj.values().all(Value::is_string) {
+        let text = obj.get("en").or_else(|| obj.values().next());
+        return text.map(format_cell).unwrap_or_default();
+    }
+    "{...}".to_string()
+}
+
+/// Shrink the widest columns one char at a time until the row (with its
+/// two-space separators) fits in `budget`, never below `MIN_COLUMN_WIDTH`
+fn fit_widths(widths: &mut [usize], budget: usize) {
+    let mut total = widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1);
+    while total > budget {
+        let Some(widest) = (0..widths.len()).max_by_key(|&i| widths[i]) else {
+            return;
+        };
+        if widths[widest] <= MIN_COLUMN_WIDTH {
+            return;
+        }
+        widths[widest] -= 1;
+        total -= 1;
+    }
+}
+
+/// Shorten to `max` characters (not bytes, so multi-byte text never splits)
+fn truncate_cell(cell: String, max: usize) -> String {
+    if cell.chars().count() > max {
+        let kept: String = cell.chars().take(max.saturating_sub(3)).collect();
+        format!("{kept}...")
+    } else {
+        cell
     }
 }
 
@@ -165,11 +561,67 @@ fn format_cell(value: &Value) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_render_template() {
+        use serde_json::json;
+        let order = json!({
+            "id": "ord_1",
+            "status": "paid",
+            "total": {"amount": 5398, "currency": "usd"},
+            "items": [{"sku": "A"}],
+            "note": null,
+        });
+        assert_eq!(
+            render_template("{id}: {status} {total.amount} {total.currency}", &order, ""),
+            "ord_1: paid 5398 usd"
+        );
+        assert_eq!(render_template("{items.0.sku}|{note}|{nope.x}", &order, "-"), "A|-|-");
+        assert_eq!(
+            render_template("{{id}} {total}", &order, ""),
+            r#"{id} {"amount":5398,"currency":"usd"}"#
+        );
+    }
+
+    #[test]
+    fn test_flatten_plain() {
+        use serde_json::json;
+        let value = json!({
+            "id": "ord_1",
+            "total": {"amount": 5398, "currency": "usd"},
+            "items": [{"sku": "A"}, {"sku": "B", "tags": []}],
+            "note": null,
+        });
+        let mut lines = Vec::new();
+        flatten_plain("", &value, &mut lines);
+        assert_eq!(
+            lines,
+            vec![
+                "id=ord_1",
+                "items[0].sku=A",
+                "items[1].sku=B",
+                "items[1].tags=[]",
+                "note=null",
+                "total.amount=5398",
+                "total.currency=usd",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_add_meta() {
+        use serde_json::json;
+        let elapsed = std::time::Duration::from_millis(123);
+        let out = add_meta(&json!({"id": "a"}), elapsed);
+        assert_eq!(out, json!({"id": "a", "_meta": {"elapsedMs": 123}}));
+        assert_eq!(add_meta(&json!([1, 2]), elapsed), json!([1, 2]));
+    }
+
     #[test]
     fn test_format_from_str() {
         assert_eq!(Format::from_str("json"), Format::Json);
         assert_eq!(Format::from_str("table"), Format::Table);
         assert_eq!(Format::from_str("plain"), Format::Plain);
+        assert_eq!(Format::from_str("jsonl"), Format::Json);
         assert_eq!(Format::from_str("JSON"), Format::Json);
         assert_eq!(Format::from_str("unknown"), Format::Json);
     }
@@ -186,4 +638,115 @@ mod tests {
         );
         assert_eq!(format_cell(&serde_json::json!({"a": 1})), "{...}");
     }
+
+    #[test]
+    fn test_format_cell_content_shapes() {
+        use serde_json::json;
+        assert_eq!(format_cell(&json!({"sr": "Zdravo", "en": "Hello"})), "Hello");
+        assert_eq!(format_cell(&json!({"sr": "Zdravo"})), "Zdravo");
+        assert_eq!(format_cell(&json!({"lat": 44.8, "lon": 20.46})), "44.8,20.46");
+        let title = json!({"type": "localized_text", "key": "title", "value": {"en": "Home"}});
+        assert_eq!(format_cell(&title), "Home");
+        assert_eq!(format_cell(&json!([title.clone(), title])), "2 blocks");
+        assert!(has_summary(&json!({"en": "Hello"})));
+        assert!(!has_summary(&json!({"nested": {"a": 1}})));
+        assert!(!has_summary(&json!([1, 2])));
+    }
+
+    #[test]
+    fn test_truncate_cell_multibyte() {
+        // Byte 37 falls inside "š"; byte slicing used to panic here
+        let cell = format!("{}šđžćč Zdravo svete", "a".repeat(36));
+        let truncated = truncate_cell(cell, 40);
+        assert_eq!(truncated, format!("{}š...", "a".repeat(36)));
+        assert_eq!(truncate_cell("čćžšđ".repeat(10), 40).chars().count(), 40);
+        assert_eq!(truncate_cell("Zdravo".into(), 40), "Zdravo");
+    }
+
+    #[test]
+    fn test_humanize_number() {
+        assert_eq!(humanize_number(5398, true), "53.98");
+        assert_eq!(humanize_number(123_456_789, true), "1,234,567.89");
+        assert_eq!(humanize_number(-5, true), "-0.05");
+        assert_eq!(humanize_number(1_000, false), "1,000");
+        assert_eq!(humanize_number(999, false), "999");
+    }
+
+    #[test]
+    fn test_is_numeric_column() {
+        use serde_json::json;
+        let items = vec![
+            json!({"id": "o1", "total": 5398, "note": null}),
+            json!({"id": "o2", "total": null, "note": null}),
+            json!({"id": "o3", "total": 100}),
+        ];
+        assert!(is_numeric_column(&items, "total"));
+        assert!(!is_numeric_column(&items, "id"));
+        assert!(!is_numeric_column(&items, "note"));
+    }
+
+    #[test]
+    fn test_fit_widths() {
+        let mut widths = vec![36, 10, 60];
+        fit_widths(&mut widths, 80);
+        assert_eq!(widths.iter().sum::<usize>() + 4, 80);
+        assert_eq!(widths[1], 10);
+
+        let mut narrow = vec![20, 20];
+        fit_widths(&mut narrow, 5);
+        assert_eq!(narrow, vec![MIN_COLUMN_WIDTH, MIN_COLUMN_WIDTH]);
+    }
+
+    #[test]
+    fn test_ndjson_lines() {
+        let list = serde_json::json!({"data": [{"id": 1}, {"id": 2}], "cursor": null});
+        assert_eq!(ndjson_lines(&list), vec![r#"{"id":1}"#, r#"{"id":2}"#]);
+        assert_eq!(ndjson_lines(&serde_json::json!([1, 2])), vec!["1", "2"]);
+        assert_eq!(ndjson_lines(&serde_json::json!({"id": 1})), vec![r#"{"id":1}"#]);
+    }
+
+    #[test]
+    fn test_success_json_shape() {
+        assert_eq!(
+            success_json("Node deleted").to_string(),
+            r#"{"message":"Node deleted","success":true}"#
+        );
+    }
+
+    fn strip_ansi(s: &str) -> String {
+        let mut out = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                for c in chars.by_ref() {
+                    if c == 'm' {
+                        break;
+                    }
+                }
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_colorize_json_matches_pretty_layout() {
+        colored::control::set_override(true);
+        let value = serde_json::json!({
+            "id": "abc",
+            "quote": "say \"hi\"",
+            "count": 42,
+            "ok": true,
+            "none": null,
+            "empty": [],
+            "nested": {"items": [1, {"a": "b"}], "obj": {}}
+        });
+        let colored = colorize_json(&value, 0);
+        assert!(colored.contains('\x1b'));
+        assert_eq!(
+            strip_ansi(&colored),
+            serde_json::to_string_pretty(&value).unwrap()
+        );
+    }
 }